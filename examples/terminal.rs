@@ -2,11 +2,14 @@ use std::path::PathBuf;
 
 use clap::Parser;
 
-use log_viewer::state::State;
+use log_viewer::{query::Query, state::State};
 
 #[derive(Debug, Parser)]
 struct Opt {
     path: PathBuf,
+    /// Only print events matching this query, e.g. `level>=warn and target:probe_rs::flashing`.
+    #[arg(short, long)]
+    query: Option<String>,
 }
 
 fn main() {
@@ -14,11 +17,23 @@ fn main() {
 
     let opt = Opt::parse();
 
+    let query = match opt.query.as_deref().map(Query::parse).transpose() {
+        Ok(query) => query.flatten(),
+        Err(error) => {
+            eprintln!("Invalid query: {error}");
+            std::process::exit(1);
+        }
+    };
+
     let content = std::fs::read_to_string(opt.path).expect("read file");
 
     let state = State::new(&content).unwrap();
 
-    for event in &state.events {
+    for event in state
+        .events
+        .iter()
+        .filter(|event| query.as_ref().is_none_or(|query| query.matches(event)))
+    {
         match event.fields.message.as_str() {
             "enter" | "exit" | "new" | "close" => {}
             _ => {
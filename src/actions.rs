@@ -0,0 +1,208 @@
+use std::collections::BTreeSet;
+
+use log_viewer::{expansion::Expansion, navigation::Navigation, state::State};
+
+/// Everything the viewer can do from the toolbar, the keyboard or the command palette.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action {
+    /// Opens a log by its gist URL or id.
+    Load,
+    /// Opens the form for uploading a new log.
+    Create,
+    /// Copies a link to the current view.
+    Share,
+    /// Downloads the events passing the filters.
+    Export,
+    /// Replaces the level filter with the given RUST_LOG directives.
+    FilterPreset(&'static str),
+    Expand(Expansion),
+    Navigate(Navigation),
+    /// Selects the next span with the given name.
+    JumpToSpan(String),
+    FocusSearch,
+    ToggleTimeMode,
+    ToggleTargets,
+    ToggleFacets,
+    ToggleTheme,
+    OpenPalette,
+    /// Shows the details of the selected event.
+    OpenDetails,
+    /// Adds or removes a bookmark on the selected event.
+    ToggleBookmark,
+    ToggleBookmarks,
+    /// Publishes a comment on the selected event to the gist.
+    Annotate,
+}
+
+/// An action as listed in the command palette.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub action: Action,
+    pub title: String,
+    /// The key bound to the action.
+    pub key: Option<&'static str>,
+}
+
+const PRESETS: [(&str, &str); 4] = [
+    ("Show warnings and errors", "warn"),
+    ("Show info and above", "info"),
+    ("Show everything", "trace"),
+    ("Debug probe-rs only", "off,probe_rs=debug"),
+];
+
+/// Keys bound to actions, the first key of an action is the one shown in the palette.
+fn bindings() -> Vec<(&'static str, Action)> {
+    let depths = ["1", "2", "3", "4", "5", "6", "7", "8", "9"]
+        .into_iter()
+        .zip(1..)
+        .map(|(key, depth)| (key, Action::Expand(Expansion::ExpandToDepth(depth))));
+    let mut bindings = vec![
+        ("j", Action::Navigate(Navigation::Down)),
+        ("ArrowDown", Action::Navigate(Navigation::Down)),
+        ("k", Action::Navigate(Navigation::Up)),
+        ("ArrowUp", Action::Navigate(Navigation::Up)),
+        ("h", Action::Navigate(Navigation::Collapse)),
+        ("ArrowLeft", Action::Navigate(Navigation::Collapse)),
+        ("l", Action::Navigate(Navigation::Expand)),
+        ("ArrowRight", Action::Navigate(Navigation::Expand)),
+        ("Enter", Action::OpenDetails),
+        (" ", Action::Navigate(Navigation::Toggle)),
+        ("[", Action::Navigate(Navigation::Parent)),
+        ("]", Action::Navigate(Navigation::NextSibling)),
+        ("n", Action::Navigate(Navigation::NextWarning)),
+        ("N", Action::Navigate(Navigation::PreviousWarning)),
+        ("/", Action::FocusSearch),
+        ("+", Action::Expand(Expansion::ExpandAll)),
+        ("-", Action::Expand(Expansion::CollapseAll)),
+        ("m", Action::Expand(Expansion::ExpandToMatches)),
+        ("w", Action::Expand(Expansion::ExpandToWarnings)),
+        ("x", Action::Expand(Expansion::CollapseEmpty)),
+        ("t", Action::ToggleTimeMode),
+        ("b", Action::ToggleBookmark),
+        ("B", Action::ToggleBookmarks),
+        ("a", Action::Annotate),
+    ];
+    bindings.extend(depths);
+    bindings
+}
+
+impl Action {
+    /// The action bound to `key`.
+    pub fn from_key(key: &str) -> Option<Self> {
+        bindings()
+            .into_iter()
+            .find(|(bound, _)| *bound == key)
+            .map(|(_, action)| action)
+    }
+
+    /// The key bound to the action.
+    pub fn key(&self) -> Option<&'static str> {
+        bindings()
+            .into_iter()
+            .find(|(_, action)| action == self)
+            .map(|(key, _)| key)
+    }
+
+    fn title(&self) -> String {
+        match self {
+            Action::Load => "Load log from gist".into(),
+            Action::Create => "Upload new log".into(),
+            Action::Share => "Copy link to this view".into(),
+            Action::Export => "Export filtered events".into(),
+            Action::FilterPreset(directives) => PRESETS
+                .iter()
+                .find(|(_, preset)| preset == directives)
+                .map(|(title, _)| format!("Filter: {title}"))
+                .unwrap_or_else(|| format!("Filter: {directives}")),
+            Action::Expand(expansion) => expansion.to_string(),
+            Action::Navigate(navigation) => match navigation {
+                Navigation::Down => "Select next row",
+                Navigation::Up => "Select previous row",
+                Navigation::Collapse => "Collapse span or go to parent",
+                Navigation::Expand => "Expand span or go to first child",
+                Navigation::Toggle => "Toggle span",
+                Navigation::Parent => "Go to parent span",
+                Navigation::NextSibling => "Go to next sibling span",
+                Navigation::NextWarning => "Go to next warning or error",
+                Navigation::PreviousWarning => "Go to previous warning or error",
+            }
+            .into(),
+            Action::JumpToSpan(name) => format!("Jump to span: {name}"),
+            Action::FocusSearch => "Search".into(),
+            Action::ToggleTimeMode => "Toggle time mode".into(),
+            Action::ToggleTargets => "Toggle targets sidebar".into(),
+            Action::ToggleFacets => "Toggle fields panel".into(),
+            Action::ToggleTheme => "Toggle dark theme".into(),
+            Action::OpenPalette => "Command palette".into(),
+            Action::OpenDetails => "Show event details".into(),
+            Action::ToggleBookmark => "Bookmark selected event".into(),
+            Action::ToggleBookmarks => "Toggle bookmarks panel".into(),
+            Action::Annotate => "Annotate selected event".into(),
+        }
+    }
+}
+
+/// Lists all actions, including a jump to each span name of `state`.
+pub fn registry(state: Option<&State>) -> Vec<Entry> {
+    let mut actions = vec![
+        Action::Load,
+        Action::Create,
+        Action::Share,
+        Action::Export,
+        Action::FocusSearch,
+        Action::OpenDetails,
+        Action::ToggleBookmark,
+        Action::ToggleBookmarks,
+        Action::Annotate,
+        Action::ToggleTimeMode,
+        Action::ToggleTargets,
+        Action::ToggleFacets,
+        Action::ToggleTheme,
+    ];
+    actions.extend(
+        PRESETS
+            .iter()
+            .map(|(_, directives)| Action::FilterPreset(directives)),
+    );
+    actions.extend(
+        [
+            Expansion::ExpandAll,
+            Expansion::CollapseAll,
+            Expansion::ExpandToDepth(1),
+            Expansion::ExpandToDepth(2),
+            Expansion::ExpandToDepth(3),
+            Expansion::ExpandToMatches,
+            Expansion::ExpandToWarnings,
+            Expansion::CollapseEmpty,
+        ]
+        .map(Action::Expand),
+    );
+    actions.extend(
+        [
+            Navigation::NextWarning,
+            Navigation::PreviousWarning,
+            Navigation::Parent,
+            Navigation::NextSibling,
+        ]
+        .map(Action::Navigate),
+    );
+    if let Some(state) = state {
+        let names = state
+            .nodes
+            .iter()
+            .filter_map(|node| node.index)
+            .filter_map(|index| state.events[index].span.as_ref())
+            .map(|span| span.name.clone())
+            .collect::<BTreeSet<_>>();
+        actions.extend(names.into_iter().map(Action::JumpToSpan));
+    }
+
+    actions
+        .into_iter()
+        .map(|action| Entry {
+            title: action.title(),
+            key: action.key(),
+            action,
+        })
+        .collect()
+}
@@ -0,0 +1,95 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+/// Name of the gist file holding the annotations of the log stored next to it.
+pub const ANNOTATIONS_FILE: &str = "annotations.json";
+
+/// A comment on an event, shared with everyone opening the gist.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Annotation {
+    /// Index of the event in the log.
+    pub event: usize,
+    pub comment: String,
+    pub author: String,
+    /// When the annotation was published, as an ISO 8601 timestamp.
+    #[serde(default)]
+    pub created_at: String,
+}
+
+/// The content of [`ANNOTATIONS_FILE`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Annotations {
+    pub version: u32,
+    /// Ordered by event, annotations of the same event in the order they were published.
+    pub annotations: Vec<Annotation>,
+}
+
+impl Default for Annotations {
+    fn default() -> Self {
+        Self {
+            version: 1,
+            annotations: vec![],
+        }
+    }
+}
+
+impl Annotations {
+    pub fn parse(content: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(content)
+    }
+
+    /// The annotations of `event`, oldest first.
+    pub fn on(&self, event: usize) -> &[Annotation] {
+        let start = self
+            .annotations
+            .partition_point(|annotation| annotation.event < event);
+        let end = self
+            .annotations
+            .partition_point(|annotation| annotation.event <= event);
+        &self.annotations[start..end]
+    }
+
+    /// Adds `annotation` after the existing annotations of its event.
+    pub fn with(mut self, annotation: Annotation) -> Self {
+        let position = self
+            .annotations
+            .partition_point(|existing| existing.event <= annotation.event);
+        self.annotations.insert(position, annotation);
+        self
+    }
+
+    /// Adds `annotation` to the annotations published so far, `published` being the content of
+    /// [`ANNOTATIONS_FILE`] if the gist has one, and returns them with the update publishing them.
+    pub fn publish(
+        published: Option<&str>,
+        annotation: Annotation,
+    ) -> serde_json::Result<(Self, UpdateGist)> {
+        let annotations = match published {
+            Some(content) => Self::parse(content)?,
+            None => Self::default(),
+        }
+        .with(annotation);
+        let update = annotations.update()?;
+        Ok((annotations, update))
+    }
+
+    /// The body of a gist update replacing [`ANNOTATIONS_FILE`], all other files are kept.
+    pub fn update(&self) -> serde_json::Result<UpdateGist> {
+        let content = serde_json::to_string_pretty(self)?;
+        Ok(UpdateGist {
+            files: BTreeMap::from([(ANNOTATIONS_FILE.into(), UpdateGistFile { content })]),
+        })
+    }
+}
+
+/// A gist update as sent to Github's v3 API, files not listed are left as they are.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UpdateGist {
+    pub files: BTreeMap<String, UpdateGistFile>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UpdateGistFile {
+    pub content: String,
+}
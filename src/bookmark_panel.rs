@@ -0,0 +1,161 @@
+use std::rc::Rc;
+
+use log_viewer::{
+    bookmarks::{BookmarkExport, Bookmarks},
+    proto::log_level::LogLevelLabel,
+    state::State,
+};
+use wasm_bindgen::JsCast;
+use web_sys::HtmlInputElement;
+use yew::{classes, function_component, html, Callback, Event, Html, Properties};
+
+use crate::download::download;
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct BookmarkPanelProps {
+    pub(crate) state: Rc<State>,
+    pub(crate) bookmarks: Rc<Bookmarks>,
+    /// The log identity written to exports.
+    pub(crate) identity: String,
+    pub(crate) on_change: Callback<Bookmarks>,
+    /// Called with the event index to select and scroll to.
+    pub(crate) on_jump: Callback<usize>,
+    pub(crate) on_close: Callback<()>,
+}
+
+/// Reads an exported bookmarks file and merges it into `bookmarks`.
+async fn import(
+    file: gloo::file::File,
+    identity: String,
+    bookmarks: Rc<Bookmarks>,
+    on_change: Callback<Bookmarks>,
+) {
+    let text = match gloo::file::futures::read_as_text(&file).await {
+        Ok(text) => text,
+        Err(error) => {
+            log::warn!("Failed to read {}: {error}", file.name());
+            return;
+        }
+    };
+    let export = match serde_json::from_str::<BookmarkExport>(&text) {
+        Ok(export) => export,
+        Err(error) => {
+            log::warn!("Invalid bookmarks file {}: {error}", file.name());
+            return;
+        }
+    };
+    // Event indices only make sense for the log they were created for.
+    if export.log != identity
+        && !gloo::dialogs::confirm("These bookmarks are from a different log, import anyway?")
+    {
+        return;
+    }
+    on_change.emit((*bookmarks).clone().merge(export.bookmarks));
+}
+
+#[function_component(BookmarkPanel)]
+pub fn bookmark_panel(props: &BookmarkPanelProps) -> Html {
+    let onexport = {
+        let export = BookmarkExport {
+            log: props.identity.clone(),
+            bookmarks: props.bookmarks.bookmarks.clone(),
+        };
+        move |_| match serde_json::to_string_pretty(&export) {
+            Ok(json) => download("bookmarks.json", "application/json", &json),
+            Err(error) => log::warn!("Failed to export bookmarks: {error}"),
+        }
+    };
+
+    let onimport = {
+        let identity = props.identity.clone();
+        let bookmarks = props.bookmarks.clone();
+        let on_change = props.on_change.clone();
+        move |event: Event| {
+            // When events are created the target is undefined, it's only
+            // when dispatched does the target get added.
+            let target = event.target();
+            // Events can bubble so this listener might catch events from child
+            // elements which are not of type HtmlInputElement
+            let Some(input) = target.and_then(|t| t.dyn_into::<HtmlInputElement>().ok()) else {
+                return;
+            };
+            let Some(file) = input.files().and_then(|files| files.get(0)) else {
+                return;
+            };
+            // Allows importing the same file again.
+            input.set_value("");
+            wasm_bindgen_futures::spawn_local(import(
+                file.into(),
+                identity.clone(),
+                bookmarks.clone(),
+                on_change.clone(),
+            ));
+        }
+    };
+
+    let onclose = {
+        let on_close = props.on_close.clone();
+        move |_| on_close.emit(())
+    };
+
+    let button = classes!["px-2", "border", "border-black"];
+
+    html! {<div class={classes!["w-80", "shrink-0", "m-3", "text-sm", "sticky", "top-0", "self-start", "max-h-screen", "overflow-y-auto"]}>
+        <div class="flex items-center">
+            <p class="grow font-bold">{"Bookmarks"}</p>
+            <button onclick={onclose} class={button.clone()}>{"Close"}</button>
+        </div>
+        <div class="flex items-center mt-2">
+            <button onclick={onexport} class={button.clone()}>{"Export"}</button>
+            <label class={classes!["ml-2", "px-2", "border", "border-black", "cursor-pointer"]}>
+                {"Import"}
+                <input type="file" accept=".json,application/json" onchange={onimport} class="hidden" />
+            </label>
+        </div>
+        {if props.bookmarks.is_empty() {
+            html!{<p class={classes!["mt-2", "text-gray-500"]}>{"Right-click an event or press b to bookmark it."}</p>}
+        } else {
+            html!{}
+        }}
+        <ul>
+            {for props.bookmarks.bookmarks.iter().filter_map(|bookmark| {
+                let event = props.state.events.get(bookmark.event)?;
+                let index = bookmark.event;
+                let level = event.level;
+
+                let onjump = {
+                    let on_jump = props.on_jump.clone();
+                    move |_| on_jump.emit(index)
+                };
+                let onremove = {
+                    let bookmarks = props.bookmarks.clone();
+                    let on_change = props.on_change.clone();
+                    move |_| on_change.emit((*bookmarks).clone().toggle(index))
+                };
+                let onnote = {
+                    let bookmarks = props.bookmarks.clone();
+                    let on_change = props.on_change.clone();
+                    move |event: Event| {
+                        // When events are created the target is undefined, it's only
+                        // when dispatched does the target get added.
+                        let target = event.target();
+                        // Events can bubble so this listener might catch events from child
+                        // elements which are not of type HtmlInputElement
+                        if let Some(input) = target.and_then(|t| t.dyn_into::<HtmlInputElement>().ok()) {
+                            on_change.emit((*bookmarks).clone().set_note(index, input.value()));
+                        }
+                    }
+                };
+
+                Some(html!{<li key={index} class="mt-2">
+                    <div class="flex items-center">
+                        <LogLevelLabel {level} />
+                        <button onclick={onjump} title="Jump to event" class={classes!["grow", "truncate", "text-left", "ml-1", "hover:underline"]}>{&event.fields.message}</button>
+                        <button onclick={onremove} title="Remove bookmark" class={classes!["ml-1", "px-1"]}>{"✕"}</button>
+                    </div>
+                    <input type="text" placeholder="Note" value={bookmark.note.clone()} onchange={onnote} class={classes!["w-full", "mt-1", "px-1", "border", "border-gray-400"]} />
+                </li>})
+            })}
+        </ul>
+    </div>}
+}
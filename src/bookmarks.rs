@@ -0,0 +1,100 @@
+use serde::{Deserialize, Serialize};
+
+use crate::view_state::Source;
+
+/// A bookmarked event with an optional note.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bookmark {
+    /// Index of the event in the log.
+    pub event: usize,
+    #[serde(default)]
+    pub note: String,
+}
+
+/// The bookmarks of one log, ordered by event.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct Bookmarks {
+    pub bookmarks: Vec<Bookmark>,
+}
+
+/// Bookmarks as exported to a file, with the log they belong to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BookmarkExport {
+    /// The [`log_identity`] of the log.
+    pub log: String,
+    pub bookmarks: Vec<Bookmark>,
+}
+
+impl Bookmarks {
+    pub fn get(&self, event: usize) -> Option<&Bookmark> {
+        self.bookmarks
+            .binary_search_by_key(&event, |bookmark| bookmark.event)
+            .ok()
+            .map(|position| &self.bookmarks[position])
+    }
+
+    pub fn contains(&self, event: usize) -> bool {
+        self.get(event).is_some()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bookmarks.is_empty()
+    }
+
+    /// Bookmarks `event`, or removes the bookmark and its note if it already is.
+    pub fn toggle(mut self, event: usize) -> Self {
+        match self
+            .bookmarks
+            .binary_search_by_key(&event, |bookmark| bookmark.event)
+        {
+            Ok(position) => {
+                self.bookmarks.remove(position);
+            }
+            Err(position) => self.bookmarks.insert(
+                position,
+                Bookmark {
+                    event,
+                    note: String::new(),
+                },
+            ),
+        }
+        self
+    }
+
+    /// Sets the note of a bookmark, bookmarking the event if needed.
+    pub fn set_note(self, event: usize, note: String) -> Self {
+        self.merge([Bookmark { event, note }])
+    }
+
+    /// Adds `bookmarks`, their notes replace the notes of existing bookmarks.
+    pub fn merge(mut self, bookmarks: impl IntoIterator<Item = Bookmark>) -> Self {
+        for bookmark in bookmarks {
+            match self
+                .bookmarks
+                .binary_search_by_key(&bookmark.event, |existing| existing.event)
+            {
+                Ok(position) => self.bookmarks[position] = bookmark,
+                Err(position) => self.bookmarks.insert(position, bookmark),
+            }
+        }
+        self
+    }
+}
+
+/// Identifies a log for storing its bookmarks.
+///
+/// A pinned revision identifies the log, the latest revision of a gist changes with every
+/// edit, so then the content is hashed instead.
+pub fn log_identity(source: &Source, content: &str) -> String {
+    match &source.revision {
+        Some(revision) => format!("gist/{}/{revision}", source.gist),
+        None => format!("sha/{:016x}", fnv1a(content.as_bytes())),
+    }
+}
+
+/// The 64 bit FNV-1a hash, stable across platforms and Rust versions unlike `DefaultHasher`.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
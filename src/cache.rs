@@ -0,0 +1,197 @@
+use gloo::storage::{LocalStorage, Storage};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{IdbDatabase, IdbRequest, IdbTransactionMode};
+
+use log_viewer::{annotations::Annotations, bookmarks::Bookmarks};
+
+use crate::gist::GistInfo;
+
+const DATABASE: &str = "log-viewer";
+const STORE: &str = "logs";
+
+/// `localStorage` key of the list of recently viewed logs.
+const RECENT_KEY: &str = "recent-logs";
+const MAX_RECENT: usize = 20;
+
+/// Returns the `localStorage` key of the bookmarks of a log.
+fn bookmarks_key(identity: &str) -> String {
+    format!("bookmarks/{identity}")
+}
+
+/// A log as stored in the IndexedDB cache.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CachedLog {
+    pub gist: GistInfo,
+    /// The `ETag` of the API response the log was loaded from, used for revalidation.
+    pub etag: Option<String>,
+    pub content: String,
+    /// Shared annotations of the log, kept separate so publishing one does not touch `content`.
+    #[serde(default)]
+    pub annotations: Annotations,
+}
+
+/// Returns the cache key for the given gist and revision.
+pub fn cache_key(id: &str, revision: Option<&str>) -> String {
+    match revision {
+        Some(revision) => format!("gist/{id}/{revision}"),
+        None => format!("gist/{id}"),
+    }
+}
+
+/// Caches fetched logs in IndexedDB, so reloading a page does not hit the Github API again.
+pub struct LogCache {
+    database: IdbDatabase,
+}
+
+impl LogCache {
+    pub async fn open() -> anyhow::Result<Self> {
+        let factory = web_sys::window()
+            .and_then(|window| window.indexed_db().ok().flatten())
+            .ok_or_else(|| anyhow::anyhow!("IndexedDB is not available"))?;
+        let request = factory.open_with_u32(DATABASE, 1).map_err(js_error)?;
+
+        let onupgradeneeded = Closure::once_into_js({
+            let request = request.clone();
+            move |_: web_sys::Event| {
+                if let Ok(database) = request.result() {
+                    let database: IdbDatabase = database.unchecked_into();
+                    if !database.object_store_names().contains(STORE) {
+                        let _ = database.create_object_store(STORE);
+                    }
+                }
+            }
+        });
+        request.set_onupgradeneeded(Some(onupgradeneeded.unchecked_ref()));
+
+        let database = wait_for(&request).await?;
+        Ok(Self {
+            database: database.unchecked_into(),
+        })
+    }
+
+    pub async fn get(&self, key: &str) -> anyhow::Result<Option<CachedLog>> {
+        let store = self
+            .database
+            .transaction_with_str_and_mode(STORE, IdbTransactionMode::Readonly)
+            .and_then(|transaction| transaction.object_store(STORE))
+            .map_err(js_error)?;
+        let request = store.get(&JsValue::from_str(key)).map_err(js_error)?;
+
+        match wait_for(&request).await?.as_string() {
+            Some(value) => Ok(Some(serde_json::from_str(&value)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn put(&self, key: &str, log: &CachedLog) -> anyhow::Result<()> {
+        let store = self
+            .database
+            .transaction_with_str_and_mode(STORE, IdbTransactionMode::Readwrite)
+            .and_then(|transaction| transaction.object_store(STORE))
+            .map_err(js_error)?;
+        let value = JsValue::from_str(&serde_json::to_string(log)?);
+        let request = store
+            .put_with_key(&value, &JsValue::from_str(key))
+            .map_err(js_error)?;
+
+        wait_for(&request).await?;
+        Ok(())
+    }
+}
+
+/// Waits for an IndexedDB request to complete and returns its result.
+async fn wait_for(request: &IdbRequest) -> anyhow::Result<JsValue> {
+    let promise = js_sys::Promise::new(&mut |resolve, reject| {
+        let onsuccess = Closure::once_into_js({
+            let request = request.clone();
+            move |_: web_sys::Event| {
+                let result = request.result().unwrap_or(JsValue::UNDEFINED);
+                let _ = resolve.call1(&JsValue::NULL, &result);
+            }
+        });
+        let onerror = Closure::once_into_js({
+            let request = request.clone();
+            move |_: web_sys::Event| {
+                let error = request
+                    .error()
+                    .ok()
+                    .flatten()
+                    .map(JsValue::from)
+                    .unwrap_or(JsValue::UNDEFINED);
+                let _ = reject.call1(&JsValue::NULL, &error);
+            }
+        });
+        request.set_onsuccess(Some(onsuccess.unchecked_ref()));
+        request.set_onerror(Some(onerror.unchecked_ref()));
+    });
+
+    JsFuture::from(promise).await.map_err(js_error)
+}
+
+fn js_error(error: JsValue) -> anyhow::Error {
+    anyhow::anyhow!("IndexedDB request failed: {error:?}")
+}
+
+/// An entry of the "recently viewed" list on the landing page.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RecentLog {
+    pub id: String,
+    pub revision: Option<String>,
+    pub description: Option<String>,
+    pub opened_at: String,
+}
+
+impl RecentLog {
+    /// The query string that opens this log again.
+    pub fn query(&self) -> String {
+        match &self.revision {
+            Some(revision) => format!("?gist={}&rev={revision}", self.id),
+            None => format!("?gist={}", self.id),
+        }
+    }
+}
+
+pub fn recent_logs() -> Vec<RecentLog> {
+    LocalStorage::get(RECENT_KEY).unwrap_or_default()
+}
+
+/// Moves the given log to the top of the recently viewed list.
+pub fn add_recent_log(gist: &GistInfo, revision: Option<&str>) {
+    let mut recent = recent_logs();
+    recent.retain(|log| log.id != gist.id || log.revision.as_deref() != revision);
+    recent.insert(
+        0,
+        RecentLog {
+            id: gist.id.clone(),
+            revision: revision.map(Into::into),
+            description: gist.description.clone(),
+            opened_at: String::from(js_sys::Date::new_0().to_iso_string()),
+        },
+    );
+    recent.truncate(MAX_RECENT);
+
+    if let Err(error) = LocalStorage::set(RECENT_KEY, recent) {
+        log::warn!("Failed to store recently viewed logs: {error}");
+    }
+}
+
+/// The bookmarks of the log with the given [`log_identity`](log_viewer::bookmarks::log_identity).
+pub fn load_bookmarks(identity: &str) -> Bookmarks {
+    LocalStorage::get(bookmarks_key(identity)).unwrap_or_default()
+}
+
+pub fn store_bookmarks(identity: &str, bookmarks: &Bookmarks) {
+    let key = bookmarks_key(identity);
+    // Keep storage free of logs that were only looked at.
+    let result = if bookmarks.is_empty() {
+        LocalStorage::delete(key);
+        Ok(())
+    } else {
+        LocalStorage::set(key, bookmarks)
+    };
+    if let Err(error) = result {
+        log::warn!("Failed to store bookmarks: {error}");
+    }
+}
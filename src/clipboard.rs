@@ -0,0 +1,19 @@
+use js_sys::{Function, Reflect};
+use wasm_bindgen::{JsCast, JsValue};
+
+/// Copies `text` to the clipboard.
+///
+/// The Clipboard API is still unstable in `web-sys`, so it is called through reflection.
+pub fn copy_text(text: &str) {
+    let result = Reflect::get(&gloo::utils::window(), &JsValue::from_str("navigator"))
+        .and_then(|navigator| Reflect::get(&navigator, &JsValue::from_str("clipboard")))
+        .and_then(|clipboard| {
+            let write_text = Reflect::get(&clipboard, &JsValue::from_str("writeText"))?;
+            write_text
+                .dyn_into::<Function>()?
+                .call1(&clipboard, &JsValue::from_str(text))
+        });
+    if let Err(error) = result {
+        log::warn!("Failed to copy to the clipboard: {error:?}");
+    }
+}
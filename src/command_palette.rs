@@ -0,0 +1,135 @@
+use std::rc::Rc;
+
+use log_viewer::fuzzy::fuzzy_score;
+use wasm_bindgen::JsCast;
+use web_sys::{HtmlInputElement, KeyboardEvent, MouseEvent};
+use yew::{
+    classes, function_component, html, use_effect_with, use_node_ref, use_state, Callback, Html,
+    InputEvent, Properties,
+};
+
+use crate::actions::{Action, Entry};
+
+/// Maximum number of entries listed at once.
+const MAX_ENTRIES: usize = 50;
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct CommandPaletteProps {
+    pub(crate) entries: Rc<Vec<Entry>>,
+    pub(crate) on_action: Callback<Action>,
+    pub(crate) on_close: Callback<()>,
+}
+
+#[function_component(CommandPalette)]
+pub fn command_palette(props: &CommandPaletteProps) -> Html {
+    let query = use_state(String::new);
+    let cursor = use_state(|| 0);
+    let input_ref = use_node_ref();
+
+    {
+        let input_ref = input_ref.clone();
+        use_effect_with((), move |_| {
+            if let Some(input) = input_ref.cast::<HtmlInputElement>() {
+                let _ = input.focus();
+            }
+        });
+    }
+
+    let mut matches = props
+        .entries
+        .iter()
+        .filter_map(|entry| Some((fuzzy_score(&query, &entry.title)?, entry)))
+        .collect::<Vec<_>>();
+    // Stable, so equally good entries keep the order of the registry.
+    matches.sort_by(|(a, _), (b, _)| b.cmp(a));
+    matches.truncate(MAX_ENTRIES);
+    let matches = matches
+        .into_iter()
+        .map(|(_, entry)| entry.clone())
+        .collect::<Vec<_>>();
+    let cursor_position = (*cursor).min(matches.len().saturating_sub(1));
+
+    let run = {
+        let on_action = props.on_action.clone();
+        let on_close = props.on_close.clone();
+        move |action: Action| {
+            on_close.emit(());
+            on_action.emit(action);
+        }
+    };
+
+    let oninput = {
+        let query = query.clone();
+        let cursor = cursor.clone();
+        move |event: InputEvent| {
+            // When events are created the target is undefined, it's only
+            // when dispatched does the target get added.
+            let target = event.target();
+            // Events can bubble so this listener might catch events from child
+            // elements which are not of type HtmlInputElement
+            let input = target
+                .and_then(|t| t.dyn_into::<HtmlInputElement>().ok())
+                .unwrap();
+            query.set(input.value());
+            cursor.set(0);
+        }
+    };
+
+    let onkeydown = {
+        let cursor = cursor.clone();
+        let on_close = props.on_close.clone();
+        let run = run.clone();
+        let selected = matches
+            .get(cursor_position)
+            .map(|entry| entry.action.clone());
+        let len = matches.len();
+        move |event: KeyboardEvent| match event.key().as_str() {
+            "ArrowDown" => {
+                event.prevent_default();
+                cursor.set((cursor_position + 1).min(len.saturating_sub(1)));
+            }
+            "ArrowUp" => {
+                event.prevent_default();
+                cursor.set(cursor_position.saturating_sub(1));
+            }
+            "Enter" => {
+                if let Some(action) = selected.clone() {
+                    run(action);
+                }
+            }
+            "Escape" => on_close.emit(()),
+            _ => (),
+        }
+    };
+
+    let onclose = {
+        let on_close = props.on_close.clone();
+        move |_| on_close.emit(())
+    };
+
+    html! {<div onclick={onclose} class={classes!["fixed", "inset-0", "z-10", "flex", "justify-center", "items-start", "pt-24", "bg-black/25"]}>
+        <div onclick={|event: MouseEvent| event.stop_propagation()} class={classes!["w-1/2", "bg-white", "border", "border-black", "rounded-md", "shadow-lg"]}>
+            <input
+                ref={input_ref}
+                {oninput}
+                {onkeydown}
+                value={(*query).clone()}
+                placeholder="Type a command"
+                class={classes!["w-full", "px-3", "py-2", "border-b", "border-black", "outline-none"]}
+            />
+            <ul class={classes!["max-h-96", "overflow-y-auto"]}>
+                {for matches.into_iter().enumerate().map(|(position, entry)| {
+                    let onclick = {
+                        let run = run.clone();
+                        let action = entry.action.clone();
+                        move |_| run(action.clone())
+                    };
+                    html!{<li {onclick} class={classes!["flex", "px-3", "py-1", "cursor-default", if position == cursor_position { "bg-blue-100" } else { "hover:bg-gray-100" }]}>
+                        <span class="grow">{&entry.title}</span>
+                        {for entry.key.map(|key| html!{<kbd class={classes!["px-1", "border", "rounded-sm", "text-gray-500"]}>{key}</kbd>})}
+                    </li>}
+                })}
+            </ul>
+        </div>
+    </div>}
+}
@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+
+use crate::{
+    state::{EventType, State},
+    view_state::ViewState,
+};
+
+/// How a message row is displayed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RowState {
+    /// The event passes the filters.
+    Shown,
+    /// The event is filtered out, but shown as context of a neighbouring event.
+    Context,
+    Hidden,
+}
+
+/// Which message rows are displayed, computed once for all rows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Visibility {
+    /// The state of each event, by event index.
+    pub rows: Vec<RowState>,
+    /// Runs of hidden events next to displayed ones, by the index of their first event,
+    /// with the events "show more" reveals.
+    pub gaps: HashMap<usize, Gap>,
+}
+
+/// Hidden events between displayed ones.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Gap {
+    /// Number of hidden events in the gap.
+    pub len: usize,
+    /// The events to reveal next, the ones closest to a displayed event.
+    pub reveal: Vec<usize>,
+}
+
+impl Visibility {
+    /// Applies the filters of `view_state` to all events of `state`.
+    ///
+    /// Only message rows are filtered, span headers are shown as long as anything inside them is.
+    /// In context mode every shown event also reveals the [`ViewState::context`] events before
+    /// and after it in the same span, like `grep -C`.
+    pub fn new(state: &State, view_state: &ViewState) -> Self {
+        let mut rows = vec![RowState::Hidden; state.events.len()];
+        let mut gaps = HashMap::new();
+        let context = view_state.context;
+
+        for node in &state.nodes {
+            let messages = node
+                .children
+                .iter()
+                .filter_map(|child| match child {
+                    EventType::Message(index) => Some(*index),
+                    EventType::Node(_) => None,
+                })
+                .collect::<Vec<_>>();
+
+            for &index in &messages {
+                rows[index] = if view_state.shows(&state.events[index]) {
+                    RowState::Shown
+                } else if view_state.revealed.contains(&index) {
+                    RowState::Context
+                } else {
+                    RowState::Hidden
+                };
+            }
+            if context == 0 {
+                continue;
+            }
+
+            for (position, &index) in messages.iter().enumerate() {
+                if rows[index] != RowState::Shown {
+                    continue;
+                }
+                let first = position.saturating_sub(context);
+                let last = (position + context).min(messages.len() - 1);
+                for &neighbour in &messages[first..=last] {
+                    if rows[neighbour] == RowState::Hidden {
+                        rows[neighbour] = RowState::Context;
+                    }
+                }
+            }
+
+            let mut position = 0;
+            while position < messages.len() {
+                if rows[messages[position]] != RowState::Hidden {
+                    position += 1;
+                    continue;
+                }
+                let start = position;
+                while position < messages.len() && rows[messages[position]] == RowState::Hidden {
+                    position += 1;
+                }
+                let gap = &messages[start..position];
+                let before = start > 0;
+                let after = position < messages.len();
+
+                // Reveal towards the following event, the lines before a failure matter most.
+                let reveal = match (before, after) {
+                    (_, true) => &gap[gap.len().saturating_sub(context)..],
+                    (true, false) => &gap[..context.min(gap.len())],
+                    (false, false) => continue,
+                };
+                gaps.insert(
+                    gap[0],
+                    Gap {
+                        len: gap.len(),
+                        reveal: reveal.to_vec(),
+                    },
+                );
+            }
+        }
+
+        Self { rows, gaps }
+    }
+
+    /// Writes the displayed events of `state` back as a log with one JSON event per line.
+    ///
+    /// Span events are always kept, so the exported log has the same tree.
+    pub fn export(&self, state: &State) -> String {
+        let mut log = String::new();
+        for (index, event) in state.events.iter().enumerate() {
+            if state.is_message(index) && self.rows.get(index) == Some(&RowState::Hidden) {
+                continue;
+            }
+            match serde_json::to_string(event) {
+                Ok(line) => {
+                    log.push_str(&line);
+                    log.push('\n');
+                }
+                Err(error) => log::warn!("Failed to export event {index}: {error}"),
+            }
+        }
+        log
+    }
+}
+
+/// Returns the `count` messages before and after `event` in the same span.
+pub fn around(state: &State, event: usize, count: usize) -> Vec<usize> {
+    let Some(parent) = state.parents.get(event).copied().flatten() else {
+        return vec![];
+    };
+    let messages = state.nodes[parent]
+        .children
+        .iter()
+        .filter_map(|child| match child {
+            EventType::Message(index) => Some(*index),
+            EventType::Node(_) => None,
+        })
+        .collect::<Vec<_>>();
+    // Span headers are not messages themselves, they reveal the messages around them.
+    let position = messages.partition_point(|index| *index < event);
+    let after = count + usize::from(messages.get(position) == Some(&event));
+    let first = position.saturating_sub(count);
+    let last = (position + after).min(messages.len());
+    messages[first..last].to_vec()
+}
@@ -0,0 +1,105 @@
+use std::rc::Rc;
+
+use log_viewer::{
+    proto::{format_value, log_level::LogLevelLabel, Span},
+    state::State,
+};
+use web_sys::MouseEvent;
+use yew::{classes, function_component, html, Callback, Html, Properties};
+
+use crate::clipboard::copy_text;
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct DetailPanelProps {
+    pub(crate) state: Rc<State>,
+    /// Index of the event to show.
+    pub(crate) index: usize,
+    /// The line of the log the event was parsed from.
+    pub(crate) raw: Option<String>,
+    pub(crate) on_close: Callback<()>,
+}
+
+fn copy_button(text: String) -> Html {
+    let onclick = move |event: MouseEvent| {
+        event.stop_propagation();
+        copy_text(&text);
+    };
+    html! {<button {onclick} title="Copy to clipboard" class={classes!["ml-2", "px-1", "text-xs", "border", "border-gray-400", "rounded-sm", "hover:bg-gray-200"]}>{"Copy"}</button>}
+}
+
+/// A labeled value with a copy button.
+fn section(label: &str, value: Html, copy: String) -> Html {
+    html! {<div class="mt-2">
+        <div class="flex items-center">
+            <span class="grow font-bold">{label}</span>
+            {copy_button(copy)}
+        </div>
+        {value}
+    </div>}
+}
+
+fn span_fields(span: &Span) -> Html {
+    html! {<ul class="pl-3">
+        {for span.fields.iter().map(|(name, value)| html!{
+            <li class="break-all"><span class="text-gray-500">{format!("{name}: ")}</span>{format_value(value)}</li>
+        })}
+    </ul>}
+}
+
+#[function_component(DetailPanel)]
+pub fn detail_panel(props: &DetailPanelProps) -> Html {
+    let Some(event) = props.state.events.get(props.index) else {
+        return html! {};
+    };
+    let level = event.level;
+    let line = props.state.lines[props.index] + 1;
+    let spans = event.spans.clone().unwrap_or_default();
+    let fields = event
+        .fields
+        .values
+        .iter()
+        .map(|(name, value)| format!("{name}={}", format_value(value)))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let raw = props
+        .raw
+        .clone()
+        .unwrap_or_else(|| serde_json::to_string(event).unwrap_or_default());
+
+    let onclose = {
+        let on_close = props.on_close.clone();
+        move |_| on_close.emit(())
+    };
+
+    html! {<div class={classes!["w-96", "shrink-0", "m-3", "text-sm", "sticky", "top-0", "self-start", "max-h-screen", "overflow-y-auto"]}>
+        <div class="flex items-center">
+            <p class="grow font-bold">{"Event"}</p>
+            <button onclick={onclose} class={classes!["px-2", "border", "border-black"]}>{"Close"}</button>
+        </div>
+        {section("Timestamp", html!{<p>{event.timestamp.clone().unwrap_or_else(|| "none".into())}</p>}, event.timestamp.clone().unwrap_or_default())}
+        {section("Level", html!{<p><LogLevelLabel {level} /></p>}, level.to_string())}
+        {section("Target", html!{<p class="break-all">{&event.target}</p>}, event.target.clone())}
+        {section("Message", html!{<pre class="whitespace-pre-wrap break-all">{&event.fields.message}</pre>}, event.fields.message.clone())}
+        {section("Fields", html!{<ul>
+            {for event.fields.values.iter().map(|(name, value)| html!{
+                <li class="flex items-start break-all">
+                    <span class="grow"><span class="text-gray-500">{format!("{name}: ")}</span>{format_value(value)}</span>
+                    {copy_button(format_value(value))}
+                </li>
+            })}
+        </ul>}, fields)}
+        {section("Spans", html!{<ol>
+            {for spans.iter().rev().map(|span| html!{
+                <li class="mt-1">
+                    <div class="flex items-center">
+                        <span class="grow">{&span.name}</span>
+                        {copy_button(serde_json::to_string(span).unwrap_or_default())}
+                    </div>
+                    {span_fields(span)}
+                </li>
+            })}
+        </ol>}, serde_json::to_string(&spans).unwrap_or_default())}
+        {section("Line", html!{<p>{line}</p>}, line.to_string())}
+        {section("Raw JSON", html!{<pre class={classes!["p-1", "whitespace-pre-wrap", "break-all", "bg-gray-100"]}>{&raw}</pre>}, raw.clone())}
+    </div>}
+}
@@ -0,0 +1,24 @@
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{Blob, BlobPropertyBag, HtmlAnchorElement, Url};
+
+/// Offers `content` as a file download named `filename`.
+pub fn download(filename: &str, mime: &str, content: &str) {
+    let result = (|| {
+        let options = BlobPropertyBag::new();
+        options.set_type(mime);
+        let parts = js_sys::Array::of1(&JsValue::from_str(content));
+        let blob = Blob::new_with_str_sequence_and_options(&parts, &options)?;
+        let url = Url::create_object_url_with_blob(&blob)?;
+
+        let anchor = gloo::utils::document()
+            .create_element("a")?
+            .dyn_into::<HtmlAnchorElement>()?;
+        anchor.set_href(&url);
+        anchor.set_download(filename);
+        anchor.click();
+        Url::revoke_object_url(&url)
+    })();
+    if let Err(error) = result {
+        log::warn!("Failed to download {filename}: {error:?}");
+    }
+}
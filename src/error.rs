@@ -0,0 +1,122 @@
+use std::fmt::Display;
+
+use gloo::net::http::Response;
+
+/// Everything that can prevent a log from being shown.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LoadError {
+    /// A query parameter of the page URL could not be parsed.
+    InvalidParameter {
+        key: String,
+        value: String,
+        reason: String,
+    },
+    /// The request could not be sent or the response could not be read.
+    Network(String),
+    /// The server answered with an unexpected status.
+    Http {
+        url: String,
+        status: u16,
+        rate_limit: Option<RateLimit>,
+    },
+    /// The log could not be prepared for uploading.
+    Encode(String),
+    /// The gist does not contain any files.
+    EmptyGist,
+    /// A log split across several files could not be reassembled.
+    Corrupt(String),
+    /// The log does not contain a single event that could be parsed.
+    Unparsable(String),
+    /// Some lines of the log could not be parsed, the events of the others are shown.
+    SkippedLines {
+        count: usize,
+        /// The first of the skipped lines and why it could not be parsed.
+        first: String,
+    },
+}
+
+impl LoadError {
+    pub fn network(error: impl Display) -> Self {
+        LoadError::Network(error.to_string())
+    }
+
+    pub fn http(response: &Response) -> Self {
+        LoadError::Http {
+            url: response.url(),
+            status: response.status(),
+            rate_limit: RateLimit::from_response(response),
+        }
+    }
+
+    pub fn corrupt(error: impl Display) -> Self {
+        LoadError::Corrupt(error.to_string())
+    }
+
+    /// Returns `true` if the request failed because the API rate limit is exhausted.
+    pub fn is_rate_limited(&self) -> bool {
+        matches!(
+            self,
+            LoadError::Http {
+                status: 403 | 429,
+                rate_limit: Some(RateLimit { remaining: 0, .. }),
+                ..
+            }
+        )
+    }
+}
+
+impl Display for LoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoadError::InvalidParameter { key, value, reason } => {
+                write!(f, "Invalid URL parameter {key}={value}: {reason}")
+            }
+            LoadError::Network(error) => write!(f, "Network error: {error}"),
+            LoadError::Http { .. } if self.is_rate_limited() => {
+                write!(f, "The Github API rate limit is exhausted")
+            }
+            LoadError::Http { url, status, .. } => {
+                write!(f, "Request to {url} failed with status {status}")
+            }
+            LoadError::Encode(error) => write!(f, "The log could not be uploaded: {error}"),
+            LoadError::EmptyGist => write!(f, "The gist does not contain any files"),
+            LoadError::Corrupt(error) => write!(f, "The uploaded log is corrupt: {error}"),
+            LoadError::Unparsable(error) => write!(f, "The log could not be parsed: {error}"),
+            LoadError::SkippedLines { count: 1, first } => {
+                write!(f, "1 line of the log could not be parsed and was skipped, {first}")
+            }
+            LoadError::SkippedLines { count, first } => write!(
+                f,
+                "{count} lines of the log could not be parsed and were skipped, the first is {first}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+/// The rate limit state reported by the Github API.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimit {
+    pub limit: u32,
+    pub remaining: u32,
+    /// Time at which the limit resets, in seconds since the Unix epoch.
+    pub reset: u64,
+}
+
+impl RateLimit {
+    fn from_response(response: &Response) -> Option<Self> {
+        let headers = response.headers();
+        let header = |name| {
+            headers
+                .get(name)
+                .and_then(|value| value.parse::<u64>().ok())
+        };
+
+        Some(Self {
+            limit: header("x-ratelimit-limit")? as u32,
+            remaining: header("x-ratelimit-remaining")? as u32,
+            reset: header("x-ratelimit-reset")?,
+        })
+    }
+}
@@ -0,0 +1,48 @@
+use log_viewer::expansion::Expansion;
+use wasm_bindgen::JsCast;
+use web_sys::HtmlInputElement;
+use yew::{classes, function_component, html, use_state, Callback, Event, Html, Properties};
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct ExpandBarProps {
+    pub(crate) on_expand: Callback<Expansion>,
+}
+
+#[function_component(ExpandBar)]
+pub fn expand_bar(props: &ExpandBarProps) -> Html {
+    let depth = use_state(|| 1);
+
+    let button = |expansion: Expansion, label: &str, key: &str| {
+        let on_expand = props.on_expand.clone();
+        html! {<button
+            onclick={move |_| on_expand.emit(expansion)}
+            title={format!("{expansion} ({key})")}
+            class={classes!["ml-3", "my-3", "px-2", "py-1", "border", "border-black"]}
+        >{label}</button>}
+    };
+
+    let on_depth = {
+        let depth = depth.clone();
+        move |event: Event| {
+            // When events are created the target is undefined, it's only
+            // when dispatched does the target get added.
+            let target = event.target();
+            // Events can bubble so this listener might catch events from child
+            // elements which are not of type HtmlInputElement
+            let input = target
+                .and_then(|t| t.dyn_into::<HtmlInputElement>().ok())
+                .unwrap();
+            depth.set(input.value().parse().unwrap_or(1));
+        }
+    };
+
+    html! {<span>
+        {button(Expansion::ExpandAll, "Expand all", "+")}
+        {button(Expansion::CollapseAll, "Collapse all", "-")}
+        <input type="number" min="0" onchange={on_depth} value={depth.to_string()} title="Depth to expand to" class={classes!["ml-3", "w-16", "px-2", "py-1", "border", "border-black"]} />
+        {button(Expansion::ExpandToDepth(*depth), "Expand to depth", "1-9")}
+        {button(Expansion::ExpandToMatches, "Matches", "m")}
+        {button(Expansion::ExpandToWarnings, "Warnings", "w")}
+        {button(Expansion::CollapseEmpty, "Collapse empty", "x")}
+    </span>}
+}
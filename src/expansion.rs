@@ -0,0 +1,118 @@
+use std::{collections::BTreeSet, fmt::Display};
+
+use crate::{
+    context::{RowState, Visibility},
+    proto::log_level::LogLevel,
+    state::{EventType, State},
+};
+
+/// A command changing which spans are expanded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Expansion {
+    ExpandAll,
+    CollapseAll,
+    /// Expands the spans up to the given nesting depth, top level spans have depth 1.
+    ExpandToDepth(usize),
+    /// Expands only the spans containing a visible search match.
+    ExpandToMatches,
+    /// Expands only the spans containing a visible warning or error.
+    ExpandToWarnings,
+    /// Collapses the expanded spans without any visible event.
+    CollapseEmpty,
+}
+
+impl Expansion {
+    /// Returns the expanded nodes after applying the command to `expanded`.
+    pub fn apply(
+        self,
+        state: &State,
+        visibility: &Visibility,
+        matches: &[usize],
+        expanded: &BTreeSet<usize>,
+    ) -> BTreeSet<usize> {
+        let visible = |index: usize| visibility.rows.get(index) != Some(&RowState::Hidden);
+
+        match self {
+            Expansion::ExpandAll => spans(state).collect(),
+            Expansion::CollapseAll => BTreeSet::new(),
+            Expansion::ExpandToDepth(depth) => {
+                let depths = depths(state);
+                spans(state).filter(|node| depths[*node] <= depth).collect()
+            }
+            Expansion::ExpandToMatches => enclosing(
+                state,
+                matches.iter().copied().filter(|index| visible(*index)),
+            ),
+            Expansion::ExpandToWarnings => enclosing(
+                state,
+                (0..state.events.len()).filter(|index| {
+                    visible(*index)
+                        && matches!(state.events[*index].level, LogLevel::Warn | LogLevel::Error)
+                }),
+            ),
+            Expansion::CollapseEmpty => {
+                let counts = visible_events(state, visibility);
+                expanded
+                    .iter()
+                    .copied()
+                    .filter(|node| counts.get(*node).is_some_and(|count| *count > 0))
+                    .collect()
+            }
+        }
+    }
+}
+
+impl Display for Expansion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Expansion::ExpandAll => write!(f, "Expand all"),
+            Expansion::CollapseAll => write!(f, "Collapse all"),
+            Expansion::ExpandToDepth(depth) => write!(f, "Expand to depth {depth}"),
+            Expansion::ExpandToMatches => write!(f, "Expand to matches"),
+            Expansion::ExpandToWarnings => write!(f, "Expand to warnings"),
+            Expansion::CollapseEmpty => write!(f, "Collapse empty spans"),
+        }
+    }
+}
+
+/// All span nodes, i.e. every node except the root.
+fn spans(state: &State) -> impl Iterator<Item = usize> + '_ {
+    (0..state.nodes.len()).filter(|node| state.nodes[*node].index.is_some())
+}
+
+/// The spans enclosing any of `events`.
+fn enclosing(state: &State, events: impl Iterator<Item = usize>) -> BTreeSet<usize> {
+    events.flat_map(|index| state.ancestors(index)).collect()
+}
+
+/// The nesting depth of every node, the root has depth 0.
+fn depths(state: &State) -> Vec<usize> {
+    let mut depths = vec![0; state.nodes.len()];
+    // Parents are always stored before their children.
+    for (index, node) in state.nodes.iter().enumerate() {
+        if let Some(parent) = node.parent {
+            depths[index] = depths[parent] + 1;
+        }
+    }
+    depths
+}
+
+/// The number of visible message rows in every node, including nested spans.
+fn visible_events(state: &State, visibility: &Visibility) -> Vec<usize> {
+    let mut counts = vec![0; state.nodes.len()];
+    for index in (0..state.nodes.len()).rev() {
+        let node = &state.nodes[index];
+        counts[index] += node
+            .children
+            .iter()
+            .filter(|child| match child {
+                EventType::Message(event) => visibility.rows.get(*event) != Some(&RowState::Hidden),
+                EventType::Node(_) => false,
+            })
+            .count();
+        if let Some(parent) = node.parent {
+            counts[parent] += counts[index];
+        }
+    }
+    counts
+}
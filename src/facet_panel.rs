@@ -0,0 +1,91 @@
+use std::rc::Rc;
+
+use yew::{classes, function_component, html, use_memo, Callback, Html, Properties};
+
+use log_viewer::{
+    facets::{facets, FieldFilter, FieldFilterMode},
+    state::State,
+};
+
+/// Values beyond this are left out, fields like addresses have thousands of them.
+const MAX_VALUES: usize = 25;
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct FacetPanelProps {
+    pub(crate) state: Rc<State>,
+    pub(crate) filters: Vec<FieldFilter>,
+    pub(crate) on_change: Callback<Vec<FieldFilter>>,
+}
+
+#[function_component(FacetPanel)]
+pub fn facet_panel(props: &FacetPanelProps) -> Html {
+    let facets = use_memo(Rc::as_ptr(&props.state) as usize, {
+        let state = props.state.clone();
+        move |_| facets(&state)
+    });
+
+    let add = |name: &str, value: &str, mode: FieldFilterMode| {
+        let filters = props.filters.clone();
+        let on_change = props.on_change.clone();
+        let filter = FieldFilter {
+            name: name.into(),
+            value: value.into(),
+            mode,
+        };
+        move |_| {
+            let mut filters = filters.clone();
+            filters.retain(|other| (&other.name, &other.value) != (&filter.name, &filter.value));
+            filters.push(filter.clone());
+            on_change.emit(filters);
+        }
+    };
+
+    let button = classes!["ml-1", "px-1", "border", "border-black", "font-mono"];
+
+    html! {<div class={classes!["w-72", "shrink-0", "m-3", "text-sm"]}>
+        <p class="font-bold">{"Fields"}</p>
+        {for props.filters.iter().enumerate().map(|(i, filter)| {
+            let onclick = {
+                let filters = props.filters.clone();
+                let on_change = props.on_change.clone();
+                move |_| {
+                    let mut filters = filters.clone();
+                    filters.remove(i);
+                    on_change.emit(filters);
+                }
+            };
+            html!{<span class={classes!["inline-block", "mr-1", "mt-1", "px-2", "py-1", "rounded-lg", "bg-gray-200"]}>
+                {filter.to_string()}
+                <button {onclick} title="Remove filter" class="ml-2">{"×"}</button>
+            </span>}
+        })}
+        {if facets.is_empty() {
+            html!{<p class="text-gray-500">{"The log has no fields."}</p>}
+        } else {
+            html!{}
+        }}
+        {for facets.iter().map(|facet| html!{
+            <details class="mt-1">
+                <summary class="cursor-pointer">
+                    {&facet.name}
+                    <span class="ml-1 text-gray-500">{format!("({})", facet.values.len())}</span>
+                </summary>
+                <ul class="pl-3">
+                    {for facet.values.iter().take(MAX_VALUES).map(|(value, count)| html!{
+                        <li class="flex items-center">
+                            <span class={classes!["grow", "truncate", "font-mono"]} title={value.clone()}>{value}</span>
+                            <span class="ml-1 text-gray-500">{count}</span>
+                            <button onclick={add(&facet.name, value, FieldFilterMode::Include)} title="Only show this value" class={button.clone()}>{"+"}</button>
+                            <button onclick={add(&facet.name, value, FieldFilterMode::Exclude)} title="Hide this value" class={button.clone()}>{"−"}</button>
+                        </li>
+                    })}
+                    {if facet.values.len() > MAX_VALUES {
+                        html!{<li class="text-gray-500">{format!("{} more", facet.values.len() - MAX_VALUES)}</li>}
+                    } else {
+                        html!{}
+                    }}
+                </ul>
+            </details>
+        })}
+    </div>}
+}
@@ -0,0 +1,117 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt::Display,
+};
+
+use crate::{
+    proto::{format_value, Event},
+    state::State,
+};
+
+/// The distinct values of a field and how many events have each of them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Facet {
+    pub name: String,
+    /// Values with their number of events, the most common first.
+    pub values: Vec<(String, usize)>,
+}
+
+/// Collects the fields of all message rows of `state`, sorted by name.
+///
+/// Fields of spans count for all messages inside them, like [`Event::field`] looks them up.
+/// Span lifecycle records are left out, they would count the span fields once more each.
+pub fn facets(state: &State) -> Vec<Facet> {
+    let mut counts: HashMap<&str, HashMap<String, usize>> = HashMap::new();
+    for (index, event) in state.events.iter().enumerate() {
+        if !state.is_message(index) {
+            continue;
+        }
+        let names = event
+            .fields
+            .values
+            .keys()
+            .chain(
+                event
+                    .span
+                    .iter()
+                    .chain(event.spans.iter().flatten())
+                    .flat_map(|span| span.fields.keys()),
+            )
+            .collect::<BTreeSet<_>>();
+
+        for name in names {
+            let Some(value) = event.field(name) else {
+                continue;
+            };
+            *counts
+                .entry(name)
+                .or_default()
+                .entry(format_value(value))
+                .or_default() += 1;
+        }
+    }
+
+    let mut facets = counts
+        .into_iter()
+        .map(|(name, values)| {
+            let mut values = values.into_iter().collect::<Vec<_>>();
+            values.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then(a.cmp(b)));
+            Facet {
+                name: name.to_string(),
+                values,
+            }
+        })
+        .collect::<Vec<_>>();
+    facets.sort_by(|a, b| a.name.cmp(&b.name));
+    facets
+}
+
+/// Whether a [`FieldFilter`] keeps or removes the events with its value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FieldFilterMode {
+    Include,
+    Exclude,
+}
+
+/// Filters events by the value of one of their fields.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FieldFilter {
+    pub name: String,
+    pub value: String,
+    pub mode: FieldFilterMode,
+}
+
+impl FieldFilter {
+    fn matches(&self, event: &Event) -> bool {
+        event
+            .field(&self.name)
+            .is_some_and(|value| format_value(value) == self.value)
+    }
+}
+
+impl Display for FieldFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.mode {
+            FieldFilterMode::Include => write!(f, "{}={}", self.name, self.value),
+            FieldFilterMode::Exclude => write!(f, "{}≠{}", self.name, self.value),
+        }
+    }
+}
+
+/// Returns `true` if `event` passes all `filters`.
+///
+/// Events must have one of the included values of each field with includes, and none of the
+/// excluded ones.
+pub fn show_fields(filters: &[FieldFilter], event: &Event) -> bool {
+    let mut included = HashMap::<&str, bool>::new();
+    for filter in filters {
+        match filter.mode {
+            FieldFilterMode::Exclude if filter.matches(event) => return false,
+            FieldFilterMode::Exclude => {}
+            FieldFilterMode::Include => {
+                *included.entry(&filter.name).or_default() |= filter.matches(event);
+            }
+        }
+    }
+    included.values().all(|included| *included)
+}
@@ -0,0 +1,41 @@
+/// Scores how well `pattern` matches `text` for the command palette, `None` if it doesn't.
+///
+/// All characters of the pattern have to appear in the text in order, ignoring case and
+/// whitespace in the pattern. Consecutive characters and characters at the start of a word
+/// score higher, the best way to place the pattern in the text counts.
+pub fn fuzzy_score(pattern: &str, text: &str) -> Option<usize> {
+    let pattern = pattern
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<Vec<_>>();
+    let text = text.chars().collect::<Vec<_>>();
+    let word_start = |position: usize| position == 0 || !text[position - 1].is_alphanumeric();
+
+    // The best score with the previous pattern character at each position of the text.
+    let mut scores = vec![Some(0); text.len()];
+    for (index, wanted) in pattern.iter().enumerate() {
+        scores = (0..text.len())
+            .map(|position| {
+                if !text[position].to_lowercase().eq(wanted.to_lowercase()) {
+                    return None;
+                }
+                let bonus = if word_start(position) { 4 } else { 1 };
+                if index == 0 {
+                    return Some(bonus);
+                }
+                (0..position)
+                    .filter_map(|previous| {
+                        let run = if previous + 1 == position { 5 } else { 0 };
+                        scores[previous].map(|score| score + bonus + run)
+                    })
+                    .max()
+            })
+            .collect();
+    }
+
+    if pattern.is_empty() {
+        Some(0)
+    } else {
+        scores.into_iter().flatten().max()
+    }
+}
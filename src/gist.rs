@@ -1,20 +1,18 @@
-use std::collections::BTreeMap;
+use std::{collections::BTreeMap, fmt::Display};
 
+use gloo::net::http::Request;
+use js_sys::{Reflect, Uint8Array};
 use serde::{Deserialize, Serialize};
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::ReadableStreamDefaultReader;
 
-// A Gist as received by Github's v3 API.
-#[derive(Serialize, Deserialize, Debug)]
-pub struct CreateGist {
-    pub public: bool,
-    pub files: BTreeMap<String, CreateGistFile>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub description: Option<String>,
-}
+use log_viewer::{
+    annotations::{Annotations, ANNOTATIONS_FILE},
+    gist_files::{Manifest, LOG_FILE, MANIFEST_FILE},
+};
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct CreateGistFile {
-    pub content: String,
-}
+use crate::error::LoadError;
 
 // A Gist as received by Github's v3 API.
 #[derive(Serialize, Deserialize, Debug)]
@@ -25,14 +23,96 @@ pub struct Gist {
     pub files: BTreeMap<String, GistFile>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
+    /// All revisions of the gist, newest first.
+    #[serde(default, skip_serializing)]
+    pub history: Vec<GistRevision>,
 }
 
 impl Gist {
+    pub fn info(&self) -> GistInfo {
+        GistInfo {
+            id: self.id.clone().unwrap_or_default(),
+            description: self.description.clone(),
+            history: self.history.clone(),
+        }
+    }
+
+    /// The file holding the log, for logs that were not split on upload.
     pub fn current_file(&self) -> Option<&GistFile> {
-        self.files.values().next()
+        self.files.get(LOG_FILE).or_else(|| {
+            self.files
+                .iter()
+                .find(|(name, _)| *name != ANNOTATIONS_FILE)
+                .map(|(_, file)| file)
+        })
+    }
+
+    /// Loads the content of [`ANNOTATIONS_FILE`], `None` if nothing was published yet.
+    pub async fn published_annotations(&self) -> Result<Option<String>, LoadError> {
+        match self.files.get(ANNOTATIONS_FILE) {
+            Some(file) => file.load(&|_, _| ()).await.map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Loads the annotations published for the log, if there are any.
+    pub async fn annotations(&self) -> Result<Annotations, LoadError> {
+        match self.published_annotations().await? {
+            Some(content) => Annotations::parse(&content)
+                .map_err(|error| LoadError::Corrupt(format!("{ANNOTATIONS_FILE}: {error}"))),
+            None => Ok(Annotations::default()),
+        }
+    }
+
+    /// Loads the complete log stored in the gist, reassembling it if it was split on upload.
+    pub async fn load_log(&self, progress: &dyn Fn(Progress)) -> Result<String, LoadError> {
+        let Some(manifest) = self.files.get(MANIFEST_FILE) else {
+            let file = self.current_file().ok_or(LoadError::EmptyGist)?;
+            return file
+                .load(&|received, total| {
+                    progress(Progress::Downloading {
+                        part: 1,
+                        parts: 1,
+                        received,
+                        total,
+                    })
+                })
+                .await;
+        };
+
+        let manifest: Manifest =
+            serde_json::from_str(&manifest.load(&|_, _| ()).await?).map_err(LoadError::corrupt)?;
+        let parts = manifest.parts.len();
+        let mut encoded = String::new();
+        for (index, name) in manifest.parts.iter().enumerate() {
+            let file = self.files.get(name).ok_or_else(|| {
+                LoadError::Corrupt(format!("part {name} is missing from the gist"))
+            })?;
+            let on_bytes = |received, total| {
+                progress(Progress::Downloading {
+                    part: index + 1,
+                    parts,
+                    received,
+                    total,
+                })
+            };
+            on_bytes(0, None);
+            encoded.push_str(&file.load(&on_bytes).await?);
+        }
+
+        progress(Progress::Decompressing);
+        manifest.decode(&encoded).map_err(LoadError::corrupt)
     }
 }
 
+/// The metadata of a gist that is kept around once its log has been loaded.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GistInfo {
+    pub id: String,
+    pub description: Option<String>,
+    pub history: Vec<GistRevision>,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct GistFile {
     pub filename: String,
@@ -40,3 +120,156 @@ pub struct GistFile {
     pub raw_url: String,
     pub truncated: bool,
 }
+
+impl GistFile {
+    /// Returns the file content, downloading it separately if the API response truncated it.
+    ///
+    /// `on_bytes` is called with the number of bytes received so far and the total size, if known.
+    pub async fn load(&self, on_bytes: &dyn Fn(usize, Option<usize>)) -> Result<String, LoadError> {
+        if !self.truncated {
+            return Ok(self.content.clone());
+        }
+
+        log::info!(
+            "File {} is truncated, downloading complete file...",
+            self.filename
+        );
+        let response = Request::get(&self.raw_url)
+            .send()
+            .await
+            .map_err(LoadError::network)?;
+        if !response.ok() {
+            return Err(LoadError::http(&response));
+        }
+
+        let total = response
+            .headers()
+            .get("content-length")
+            .and_then(|length| length.parse().ok());
+        let Some(body) = response.body() else {
+            return response.text().await.map_err(LoadError::network);
+        };
+
+        let reader: ReadableStreamDefaultReader = body.get_reader().unchecked_into();
+        let mut bytes = Vec::with_capacity(total.unwrap_or_default());
+        loop {
+            let chunk = JsFuture::from(reader.read())
+                .await
+                .map_err(|e| LoadError::Network(format!("{e:?}")))?;
+            let done = Reflect::get(&chunk, &"done".into())
+                .ok()
+                .and_then(|done| done.as_bool())
+                .unwrap_or(true);
+            if done {
+                break;
+            }
+            if let Ok(value) = Reflect::get(&chunk, &"value".into()) {
+                bytes.extend(value.unchecked_into::<Uint8Array>().to_vec());
+            }
+            on_bytes(bytes.len(), total);
+        }
+
+        String::from_utf8(bytes).map_err(LoadError::corrupt)
+    }
+}
+
+/// Abbreviates a revision SHA to its first 7 characters, like git does.
+pub fn short_sha(sha: &str) -> String {
+    sha.chars().take(7).collect()
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GistRevision {
+    pub version: String,
+    pub committed_at: String,
+    #[serde(default)]
+    pub change_status: ChangeStatus,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct ChangeStatus {
+    #[serde(default)]
+    pub additions: usize,
+    #[serde(default)]
+    pub deletions: usize,
+}
+
+/// Progress of a gist upload or download.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Progress {
+    Compressing,
+    Uploading {
+        files: usize,
+    },
+    Downloading {
+        part: usize,
+        parts: usize,
+        /// Bytes of the current part received so far.
+        received: usize,
+        total: Option<usize>,
+    },
+    Decompressing,
+}
+
+impl Progress {
+    /// Returns the completed fraction of the current operation, if it is known.
+    pub fn fraction(&self) -> Option<f64> {
+        match self {
+            Progress::Downloading {
+                part,
+                parts,
+                received,
+                total: Some(total),
+            } if *total > 0 => {
+                let current = (*received as f64 / *total as f64).min(1.0);
+                Some((*part as f64 - 1.0 + current) / *parts as f64)
+            }
+            Progress::Downloading { part, parts, .. } if *parts > 1 => {
+                Some((*part as f64 - 1.0) / *parts as f64)
+            }
+            _ => None,
+        }
+    }
+}
+
+fn format_size(bytes: usize) -> String {
+    if bytes >= 1024 * 1024 {
+        format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+    } else {
+        format!("{:.0} kB", bytes as f64 / 1024.0)
+    }
+}
+
+impl Display for Progress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Progress::Compressing => write!(f, "Compressing log ..."),
+            Progress::Uploading { files: 1 } => write!(f, "Uploading log ..."),
+            Progress::Uploading { files } => write!(f, "Uploading log in {files} files ..."),
+            Progress::Downloading {
+                part,
+                parts,
+                received,
+                total,
+            } => {
+                if *parts == 1 {
+                    write!(f, "Loading file")?;
+                } else {
+                    write!(f, "Loading part {part} of {parts}")?;
+                }
+                match (received, total) {
+                    (0, _) => {}
+                    (received, Some(total)) => write!(
+                        f,
+                        " ({} of {})",
+                        format_size(*received),
+                        format_size(*total)
+                    )?,
+                    (received, None) => write!(f, " ({})", format_size(*received))?,
+                }
+                write!(f, " ...")
+            }
+            Progress::Decompressing => write!(f, "Decompressing log ..."),
+        }
+    }
+}
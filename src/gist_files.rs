@@ -0,0 +1,120 @@
+//! The files a log is stored in when it is uploaded to a gist.
+
+use std::{
+    collections::BTreeMap,
+    io::{Read, Write},
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use serde::{Deserialize, Serialize};
+
+/// Name of the file used for logs that fit into a single gist file.
+pub const LOG_FILE: &str = "trace.json";
+
+/// Name of the file describing how a large log was split across the gist.
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// Github truncates file contents above 1 MB in API responses, so larger logs
+/// are compressed and split into parts that stay below this size.
+pub const MAX_FILE_SIZE: usize = 900 * 1024;
+
+// A Gist as received by Github's v3 API.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CreateGist {
+    pub public: bool,
+    pub files: BTreeMap<String, CreateGistFile>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+impl CreateGist {
+    /// Creates a gist holding `content`.
+    ///
+    /// Small logs are stored as-is, larger ones are gzip compressed, base64 encoded
+    /// and split into several files that are listed in a [`Manifest`].
+    pub fn new(content: &str, description: Option<String>) -> anyhow::Result<Self> {
+        let mut files = BTreeMap::new();
+
+        if content.len() <= MAX_FILE_SIZE {
+            files.insert(
+                LOG_FILE.into(),
+                CreateGistFile {
+                    content: content.into(),
+                },
+            );
+        } else {
+            let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
+            encoder.write_all(content.as_bytes())?;
+            let encoded = STANDARD.encode(encoder.finish()?);
+
+            let mut parts = vec![];
+            // Base64 output is pure ASCII, so splitting on byte boundaries is fine.
+            for (index, chunk) in encoded.as_bytes().chunks(MAX_FILE_SIZE).enumerate() {
+                let name = format!("{LOG_FILE}.gz.b64.{index:03}");
+                files.insert(
+                    name.clone(),
+                    CreateGistFile {
+                        content: String::from_utf8_lossy(chunk).into_owned(),
+                    },
+                );
+                parts.push(name);
+            }
+
+            let manifest = Manifest {
+                version: 1,
+                encoding: Encoding::GzipBase64,
+                size: content.len(),
+                parts,
+            };
+            files.insert(
+                MANIFEST_FILE.into(),
+                CreateGistFile {
+                    content: serde_json::to_string_pretty(&manifest)?,
+                },
+            );
+        }
+
+        Ok(Self {
+            public: true,
+            files,
+            description,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CreateGistFile {
+    pub content: String,
+}
+
+/// Describes a log that was compressed and split across several gist files.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Manifest {
+    pub version: u32,
+    pub encoding: Encoding,
+    /// Size of the uncompressed log in bytes.
+    pub size: usize,
+    /// Names of the files holding the encoded log, in order.
+    pub parts: Vec<String>,
+}
+
+impl Manifest {
+    /// Reassembles the log from the concatenated content of its parts.
+    pub fn decode(&self, encoded: &str) -> anyhow::Result<String> {
+        match self.encoding {
+            Encoding::GzipBase64 => {
+                let compressed = STANDARD.decode(encoded)?;
+                let mut content = String::with_capacity(self.size);
+                GzDecoder::new(&compressed[..]).read_to_string(&mut content)?;
+                Ok(content)
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "kebab-case")]
+pub enum Encoding {
+    GzipBase64,
+}
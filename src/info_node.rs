@@ -1,145 +1,482 @@
 use std::rc::Rc;
 
+use regex::Regex;
+use web_sys::MouseEvent;
 use yew::{
-    classes, function_component, html, use_state, Callback, Html, Properties, UseStateHandle,
+    classes, function_component, html, use_context, use_memo, Callback, Html, Properties,
+    UseStateHandle,
 };
 
-use crate::{context_menu::ContextMenuItemProps, pill::Pill};
+use crate::{
+    clipboard::copy_text,
+    context_menu::{ContextMenuAction, ContextMenuContext, ContextMenuItemProps},
+    pill::Pill,
+};
 
 use log_viewer::{
-    level_filter::LevelFilter,
-    proto::log_level::{LogLevel, LogLevelLabel},
-    state::{EventType, State},
+    annotations::Annotations,
+    bookmarks::Bookmarks,
+    context::{around, RowState, Visibility},
+    level_filter::{LevelFilter, SpanRule},
+    permalink::permalink,
+    proto::{
+        format_value,
+        log_level::{LogLevel, LogLevelLabel},
+    },
+    rows::Row,
+    search::{event_matches, highlight_ranges, SearchQuery, TextFilter, TextFilterMode},
+    state::State,
+    view_state::ViewState,
 };
 
+/// Height of every row in pixels, so the position of a row follows from its index.
+pub const ROW_HEIGHT: f64 = 32.0;
+
+/// Number of events "Show around this" reveals on each side.
+const AROUND: usize = 10;
+
+/// Renders `text` with all matches of `matcher` marked.
+fn highlight(text: &str, matcher: Option<&Regex>) -> Html {
+    let Some(matcher) = matcher else {
+        return html! {{text}};
+    };
+    let mut html = vec![];
+    let mut end = 0;
+    for range in highlight_ranges(text, matcher) {
+        html.push(html! {{&text[end..range.start]}});
+        html.push(html! {<mark class="bg-yellow-300">{&text[range.clone()]}</mark>});
+        end = range.end;
+    }
+    html.push(html! {{&text[end..]}});
+    html.into_iter().collect()
+}
+
 #[derive(Clone, PartialEq, Properties)]
 pub struct InfoNodeProps {
     pub state: Rc<State>,
-    pub node_index: usize,
-    pub level_filter: UseStateHandle<LevelFilter>,
+    pub row: Row,
+    pub view_state: UseStateHandle<ViewState>,
+    pub visibility: Rc<Visibility>,
+    /// Called with the event index when a row is clicked, to show its details.
+    pub on_open: Callback<usize>,
+    pub bookmarks: Rc<Bookmarks>,
+    /// Called with the event index to add or remove a bookmark.
+    pub on_bookmark: Callback<usize>,
+    pub annotations: Rc<Annotations>,
+    /// Called with the event index to publish an annotation on it.
+    pub on_annotate: Callback<usize>,
+}
+
+/// A context menu item applying `update` to the view state, it returns `None` for no change.
+fn menu_item(
+    view_state: &UseStateHandle<ViewState>,
+    title: String,
+    update: impl Fn(&ViewState) -> Option<ViewState> + 'static,
+) -> ContextMenuItemProps {
+    let view_state = view_state.clone();
+    ContextMenuItemProps {
+        callback: Callback::from(move |_| {
+            if let Some(new_state) = update(&view_state) {
+                view_state.set(new_state);
+            }
+        }),
+        title,
+    }
+}
+
+/// The context menu of the message row or span header of event `index`.
+fn row_menu(props: &InfoNodeProps, index: usize) -> Vec<ContextMenuItemProps> {
+    let event = &props.state.events[index];
+
+    // Only rows of the tree have a menu, so anything but a message is a span header.
+    let header = !props.state.is_message(index);
+    let mut items = vec![];
+    if header {
+        let span = event.span.as_ref().map(|span| {
+            let fields = span
+                .fields
+                .iter()
+                .map(|(name, value)| format!("{name}={}", format_value(value)))
+                .collect::<Vec<_>>();
+            format!("{}{{{}}}", span.name, fields.join(","))
+        });
+        items.push(menu_item(
+            &props.view_state,
+            "Copy span".into(),
+            move |_| {
+                copy_text(span.as_deref().unwrap_or_default());
+                None
+            },
+        ));
+    } else {
+        let message = event.fields.message.clone();
+        items.push(menu_item(
+            &props.view_state,
+            "Copy message".into(),
+            move |_| {
+                copy_text(&message);
+                None
+            },
+        ));
+    }
+
+    let json = serde_json::to_string(event).unwrap_or_default();
+    items.push(menu_item(
+        &props.view_state,
+        "Copy as JSON".into(),
+        move |_| {
+            copy_text(&json);
+            None
+        },
+    ));
+    items.push(menu_item(
+        &props.view_state,
+        "Copy permalink".into(),
+        move |_| {
+            match gloo::utils::window().location().href() {
+                Ok(href) => copy_text(&permalink(&href, index)),
+                Err(error) => log::warn!("Failed to get the link: {error:?}"),
+            }
+            None
+        },
+    ));
+
+    if let Some(span) = &event.span {
+        let rule = SpanRule::exact(span, LogLevel::Trace);
+        items.push(menu_item(
+            &props.view_state,
+            format!("Show only this {} span", span.name),
+            move |view_state| {
+                let filter = LevelFilter::from_directives("off")
+                    .expect("a valid directive")
+                    .add_span_rule(rule.clone());
+                Some(ViewState {
+                    filter,
+                    ..view_state.clone()
+                })
+            },
+        ));
+    }
+
+    if !header {
+        let message = event.fields.message.clone();
+        let label = message.chars().take(30).collect::<String>();
+        let ellipsis = if label.len() < message.len() {
+            "…"
+        } else {
+            ""
+        };
+        items.push(menu_item(
+            &props.view_state,
+            format!("Hide all \"{label}{ellipsis}\""),
+            move |view_state| {
+                let mut text_filters = view_state.text_filters.clone();
+                text_filters.push(TextFilter {
+                    query: SearchQuery::exact(&message),
+                    mode: TextFilterMode::Hide,
+                    target: None,
+                    message_only: true,
+                });
+                Some(ViewState {
+                    text_filters,
+                    ..view_state.clone()
+                })
+            },
+        ));
+    }
+
+    let target = event.target.clone();
+    let level = event.level;
+    items.push(menu_item(
+        &props.view_state,
+        format!("Set {target} to {level}"),
+        move |view_state| {
+            Some(ViewState {
+                filter: view_state
+                    .filter
+                    .clone()
+                    .set_level(Some(target.clone()), level),
+                ..view_state.clone()
+            })
+        },
+    ));
+
+    let on_bookmark = props.on_bookmark.clone();
+    let title = if props.bookmarks.contains(index) {
+        "Remove bookmark"
+    } else {
+        "Bookmark"
+    };
+    items.push(menu_item(&props.view_state, title.into(), move |_| {
+        on_bookmark.emit(index);
+        None
+    }));
+    let on_annotate = props.on_annotate.clone();
+    items.push(menu_item(
+        &props.view_state,
+        "Annotate…".into(),
+        move |_| {
+            on_annotate.emit(index);
+            None
+        },
+    ));
+
+    let state = props.state.clone();
+    items.push(menu_item(
+        &props.view_state,
+        format!("Show {AROUND} events around this"),
+        move |view_state| {
+            let mut revealed = view_state.revealed.clone();
+            revealed.extend(around(&state, index, AROUND));
+            Some(ViewState {
+                revealed,
+                ..view_state.clone()
+            })
+        },
+    ));
+
+    items
 }
 
+/// A pill with a context menu for showing only or hiding the target prefix `targets`.
+fn nest(
+    view_state: UseStateHandle<ViewState>,
+    level: LogLevel,
+    targets: &str,
+    target: &str,
+) -> Html {
+    let context_menu = vec![
+        ContextMenuItemProps {
+            callback: {
+                let view_state = view_state.clone();
+                let targets = targets.to_string();
+                Callback::from(move |_| {
+                    view_state.set(ViewState {
+                        filter: view_state
+                            .filter
+                            .clone()
+                            .set_level(None, LogLevel::None)
+                            .set_level(Some(targets.to_string()), level),
+                        ..(*view_state).clone()
+                    });
+                })
+            },
+            title: format!("Only show {targets}"),
+        },
+        ContextMenuItemProps {
+            callback: {
+                let view_state = view_state;
+                let targets = targets.to_string();
+                Callback::from(move |_| {
+                    view_state.set(ViewState {
+                        filter: view_state
+                            .filter
+                            .clone()
+                            .set_level(Some(targets.to_string()), LogLevel::None),
+                        ..(*view_state).clone()
+                    })
+                })
+            },
+            title: format!("Don't show {targets}"),
+        },
+    ];
+    let classes = classes!["p-1", "-m-1", "rounded-md", "hover:bg-gray-200"];
+
+    html! { <Pill {context_menu} {classes}>{target}</Pill> }
+}
+
+/// Renders a single row of the flattened event tree.
+///
+/// Every row is exactly [`ROW_HEIGHT`] high, longer content is clipped, the detail panel
+/// shows all of it.
 #[function_component(InfoNode)]
 pub fn info_node(props: &InfoNodeProps) -> Html {
-    let node = &props.state.nodes[props.node_index];
-    let event = node.index.map(|i| &props.state.events[i]);
-    let collapsed = use_state(|| node.expanded);
+    let context_menu = use_context::<ContextMenuContext>().unwrap();
+    // The items are only built when the menu is opened, most rows never need them.
+    let oncontextmenu = |index: usize| {
+        let context_menu = context_menu.clone();
+        let props = props.clone();
+        move |event: MouseEvent| {
+            event.prevent_default();
+            event.set_cancel_bubble(true);
+            context_menu.dispatch(ContextMenuAction::Show(
+                event.page_x(),
+                event.page_y(),
+                row_menu(&props, index),
+            ))
+        }
+    };
+    let matcher = use_memo(props.view_state.search.clone(), |search| {
+        (!search.is_empty())
+            .then(|| search.matcher().ok())
+            .flatten()
+    });
+    let matcher = matcher.as_ref().as_ref();
 
-    let onclick = {
-        let collapsed = collapsed.clone();
-        move |_| {
-            collapsed.set(!*collapsed);
+    let time = |timestamp: Option<&str>| match props
+        .view_state
+        .time_mode
+        .format(timestamp, props.state.start)
+    {
+        Some(time) => html! {<span class={classes!["mr-1", "p-1", "text-gray-500"]}>{time}</span>},
+        None => html! {},
+    };
+
+    let bookmark = |index: usize| match props.bookmarks.get(index) {
+        Some(bookmark) => {
+            let title = if bookmark.note.is_empty() {
+                "Bookmarked".into()
+            } else {
+                bookmark.note.clone()
+            };
+            html! {<span {title} class={classes!["mr-1", "p-1", "text-blue-500"]}>{"★"}</span>}
         }
+        None => html! {},
     };
 
-    let body = || {
-        html! {{
-            node.children.iter().map(|child| {
-                match child {
-                    EventType::Message(message) => {
-                        let event = &props.state.events[*message];
-                        let message = &event.fields.message;
-                        let level = event.level;
-                        let target = &event.target;
-                        let hidden = !props.level_filter.show(Some(target.clone()), &level);
-                        let targets = &target.split("::").collect::<Vec<_>>();
-                        let level_filter = props.level_filter.clone();
-
-                        fn nest(level_filter: UseStateHandle<LevelFilter>, level: LogLevel, targets: &str, target: &str) -> Html {
-                            let context_menu = vec![
-                                ContextMenuItemProps {
-                                    callback: {
-                                        let level_filter = level_filter.clone();
-                                        let targets = targets.to_string();
-                                        Callback::from(move |_| {
-                                            level_filter
-                                                .set((*level_filter)
-                                                .clone()
-                                                .set_level(None, LogLevel::None)
-                                                .set_level(Some(targets.to_string()), level));
-                                        })
-                                    },
-                                    title: format!("Only show {targets}"),
-                                },
-                                ContextMenuItemProps {
-                                    callback: {
-                                        let level_filter = level_filter;
-                                        let targets = targets.to_string();
-                                        Callback::from(move |_| {
-                                        level_filter.set((*level_filter).clone().set_level(Some(targets.to_string()), LogLevel::None))
-                                    })},
-                                    title: format!("Don't show {targets}"),
-                                },
-                            ];
-                            let classes = classes!["p-1", "-m-1", "rounded-md", "hover:bg-gray-200"];
-
-                            html!{ <Pill {context_menu} {classes}>{target}</Pill> }
-                        }
-
-                        html! {<span class={classes!["pl-6", "py-1", "m-1", "flex", "cursor-default", "select-none", if hidden { "hidden" } else { "block" }]}>
-                            <LogLevelLabel {level} />
-                            <span class={classes!["p-1", "px-2", "rounded-lg", format!("bg-{}", level.color())]}>
-                            {for targets.iter().enumerate().scan(String::new(), |state, (i, target)| {
-                                if i == 0 {
-                                    state.push_str(target);
-                                } else {
-                                    *state = format!("{state}::{target}");
-                                }
-                                Some((i, state.clone(), target))}).map(|(i, ts, t)| { html!{<>
-                                    {if i != 0 {
-                                        html!{{"::"}}
-                                    } else {
-                                        html!{}
-                                    }}
-                                    { nest(level_filter.clone(), level, &ts, t) }
-                                </>}})
-                            }
-                            </span>
-                            <pre>
-                            {message}
-                            </pre>
-                        </span>}
+    let row = classes![
+        "flex",
+        "items-center",
+        "overflow-hidden",
+        "whitespace-nowrap"
+    ];
+    let style = format!(
+        "height: {ROW_HEIGHT}px; padding-left: {}rem",
+        1.5 * props.row.depth() as f64
+    );
 
-                    }
-                    EventType::Node(node_index) => html! {
-                        <InfoNode
-                            key={*node_index}
-                            state={props.state.clone()}
-                            node_index={node_index}
-                            level_filter={props.level_filter.clone()}
-                        />
-                    },
+    match props.row {
+        Row::Gap { event, .. } => {
+            let Some(gap) = props.visibility.gaps.get(&event) else {
+                return html! {};
+            };
+            let onclick = {
+                let view_state = props.view_state.clone();
+                let reveal = gap.reveal.clone();
+                move |_| {
+                    let mut revealed = view_state.revealed.clone();
+                    revealed.extend(reveal.iter().copied());
+                    view_state.set(ViewState {
+                        revealed,
+                        ..(*view_state).clone()
+                    })
                 }
-            }).collect::<Html>()
-        }}
-    };
+            };
+            html! {<div class={row} {style}>
+                <button {onclick} class={classes!["pl-6", "text-sm", "text-gray-500", "hover:text-black"]}>
+                    {format!("··· show {} more ({} hidden)", gap.reveal.len(), gap.len)}
+                </button>
+            </div>}
+        }
+        Row::Message { event: index, .. } => {
+            let event = &props.state.events[index];
+            let message = &event.fields.message;
+            let level = event.level;
+            let target = &event.target;
+            let context = props.visibility.rows[index] == RowState::Context;
+            let selected = props.view_state.selected == Some(index);
+            let matched = matcher.is_some_and(|matcher| event_matches(event, matcher));
+            let targets = &target.split("::").collect::<Vec<_>>();
+            let view_state = props.view_state.clone();
 
-    if let Some(event) = event {
-        let span_title = event.span.as_ref().unwrap().name.clone();
-        let level = event.level;
-        let target = &event.target;
+            let onclick = {
+                let on_open = props.on_open.clone();
+                move |_| on_open.emit(index)
+            };
 
-        html! {
-            <div class="flex w-full py-1">
-                <div class="flex flex-col">
-                    <svg xmlns="http://www.w3.org/2000/svg" onclick={onclick.clone()} fill="none" viewBox="0 0 24 24" stroke-width="1.5" stroke="currentColor" class={classes!("w-6", "h-6", if !*collapsed { "block" } else { "hidden" } )}>
-                        <path stroke-linecap="round" stroke-linejoin="round" d="M8.25 4.5l7.5 7.5-7.5 7.5" />
-                    </svg>
+            html! {
+            <div {onclick} oncontextmenu={oncontextmenu(index)} {style} class={classes![row, "pl-6", "cursor-default", "select-none", context.then_some("opacity-50"), if selected { Some("bg-blue-100") } else { matched.then_some("bg-yellow-50") }]}>
+                {bookmark(index)}
+                {time(event.timestamp.as_deref())}
+                <LogLevelLabel {level} />
+                <span class={classes!["p-1", "px-2", "rounded-lg", format!("bg-{}", level.color())]}>
+                {for targets.iter().enumerate().scan(String::new(), |state, (i, target)| {
+                    if i == 0 {
+                        state.push_str(target);
+                    } else {
+                        *state = format!("{state}::{target}");
+                    }
+                    Some((i, state.clone(), target))}).map(|(i, ts, t)| { html!{<>
+                        {if i != 0 {
+                            html!{{"::"}}
+                        } else {
+                            html!{}
+                        }}
+                        { nest(view_state.clone(), level, &ts, t) }
+                    </>}})
+                }
+                </span>
+                <pre class={classes!["ml-1", "truncate"]}>
+                {highlight(message, matcher)}
+                </pre>
+            </div>
+            }
+        }
+        Row::Span {
+            node,
+            event: index,
+            expanded,
+            ..
+        } => {
+            let event = &props.state.events[index];
+            let span_title = event
+                .span
+                .as_ref()
+                .map(|span| span.name.clone())
+                .unwrap_or_default();
+            let level = event.level;
+            let target = &event.target;
+            let selected = props.view_state.selected == Some(index);
 
-                    <svg xmlns="http://www.w3.org/2000/svg" {onclick} fill="none" viewBox="0 0 24 24" stroke-width="1.5" stroke="currentColor" class={classes!("w-6", "h-6", if *collapsed { "block" } else { "hidden" } )}>
-                        <path stroke-linecap="round" stroke-linejoin="round" d="M19.5 8.25l-7.5 7.5-7.5-7.5" />
+            let onclick = {
+                let view_state = props.view_state.clone();
+                move |_| {
+                    let mut expanded = view_state.expanded.clone();
+                    if !expanded.remove(&node) {
+                        expanded.insert(node);
+                    }
+                    view_state.set(ViewState {
+                        expanded,
+                        ..(*view_state).clone()
+                    });
+                }
+            };
+            let open = {
+                let on_open = props.on_open.clone();
+                move |_| on_open.emit(index)
+            };
+            let chevron = if expanded {
+                "M19.5 8.25l-7.5 7.5-7.5-7.5"
+            } else {
+                "M8.25 4.5l7.5 7.5-7.5 7.5"
+            };
+
+            html! {
+                <div class={row} {style}>
+                    <svg xmlns="http://www.w3.org/2000/svg" {onclick} fill="none" viewBox="0 0 24 24" stroke-width="1.5" stroke="currentColor" class={classes!("w-6", "h-6", "shrink-0")}>
+                        <path stroke-linecap="round" stroke-linejoin="round" d={chevron} />
                     </svg>
-                    <div class={classes!("grow", "w-3", "border-r", "border-black", if *collapsed { "block" } else { "hidden" } )}></div>
-                </div>
-                <div>
-                    <LogLevelLabel {level} />
-                    <span class={classes!["m-1","p-1", "rounded-md", "bg-gray-200"]}>{target}</span>
-                    {span_title}
-                    <span class={classes!["pt-1", if *collapsed { "block" } else { "hidden" } ]}>{body()}</span>
+                    <span onclick={open} oncontextmenu={oncontextmenu(index)} class={classes!["cursor-default", "truncate", selected.then_some("bg-blue-100")]}>
+                        {bookmark(index)}
+                        {time(event.timestamp.as_deref())}
+                        <LogLevelLabel {level} />
+                        <span class={classes!["m-1","p-1", "rounded-md", "bg-gray-200"]}>{highlight(target, matcher)}</span>
+                        {highlight(&span_title, matcher)}
+                    </span>
                 </div>
-            </div>
+            }
+        }
+        Row::Annotation { event, number, .. } => {
+            let Some(annotation) = props.annotations.on(event).get(number) else {
+                return html! {};
+            };
+            let title = format!("{}\n{}", annotation.created_at, annotation.comment);
+            html! {<div class={row} {style}>
+                <span {title} class={classes!["ml-6", "px-2", "py-1", "text-sm", "truncate", "border-l-4", "border-blue-400", "bg-blue-50"]}>
+                    <span class="font-bold">{format!("{}: ", annotation.author)}</span>
+                    {&annotation.comment}
+                </span>
+            </div>}
         }
-    } else {
-        body()
     }
 }
@@ -1,64 +1,598 @@
-use std::{cmp::Ordering, collections::HashMap};
+use std::{borrow::Cow, collections::HashMap, fmt::Display, str::FromStr};
 
-use crate::proto::log_level::LogLevel;
+use crate::proto::{format_value, log_level::LogLevel, Event, Span};
+
+/// The levels that can be selected, from the most to the least verbose.
+pub const LEVELS: [LogLevel; 5] = [
+    LogLevel::Trace,
+    LogLevel::Debug,
+    LogLevel::Info,
+    LogLevel::Warn,
+    LogLevel::Error,
+];
+
+/// Which levels of a target are shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Levels {
+    /// The level and all more severe ones.
+    AtLeast(LogLevel),
+    /// Exactly the levels of the set.
+    Only(LevelSet),
+}
+
+impl Levels {
+    pub fn contains(&self, level: &LogLevel) -> bool {
+        match self {
+            Levels::AtLeast(min) => level >= min,
+            Levels::Only(set) => set.contains(level),
+        }
+    }
+
+    /// The shown levels as a set.
+    pub fn to_set(self) -> LevelSet {
+        match self {
+            Levels::AtLeast(min) => LEVELS.iter().filter(|level| **level >= min).collect(),
+            Levels::Only(set) => set,
+        }
+    }
+}
+
+/// A set of log levels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct LevelSet(u8);
+
+impl LevelSet {
+    pub fn contains(&self, level: &LogLevel) -> bool {
+        self.0 & (1 << level.num()) != 0
+    }
+
+    pub fn insert(&mut self, level: LogLevel) {
+        self.0 |= 1 << level.num();
+    }
+
+    pub fn remove(&mut self, level: &LogLevel) {
+        self.0 &= !(1 << level.num());
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns the levels of the set, from the most to the least verbose.
+    pub fn iter(&self) -> impl Iterator<Item = LogLevel> + '_ {
+        LEVELS.into_iter().filter(|level| self.contains(level))
+    }
+}
+
+impl<'a> FromIterator<&'a LogLevel> for LevelSet {
+    fn from_iter<T: IntoIterator<Item = &'a LogLevel>>(iter: T) -> Self {
+        let mut set = LevelSet::default();
+        for level in iter {
+            set.insert(*level);
+        }
+        set
+    }
+}
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct LevelFilter {
+    /// The threshold of each target, for targets with a set of levels the least severe one.
     matrix: HashMap<Option<String>, LogLevel>,
+    /// Targets showing exactly these levels instead of a threshold.
+    sets: HashMap<Option<String>, LevelSet>,
+    /// The matrix split at `::`, so lookups don't have to allocate.
+    trie: TrieNode,
+    /// Rules for events inside of spans, these take precedence over the matrix.
+    span_rules: Vec<SpanRule>,
 }
 
-impl LevelFilter {
-    pub fn show(&self, target: Option<String>, level: &LogLevel) -> bool {
-        let target = self
-            .matrix
-            .iter()
-            .filter_map(|(key, _)| match (&target, key) {
-                (Some(target), Some(key)) => {
-                    if target.starts_with(key) {
-                        Some(Some(key.clone()))
-                    } else {
-                        None
-                    }
-                }
-                (_, None) => Some(key.clone()),
-                _ => None,
-            })
-            .max_by(|a, b| match (a, b) {
-                (None, None) => Ordering::Equal,
-                (None, Some(_)) => Ordering::Less,
-                (Some(_), None) => Ordering::Greater,
-                (Some(a), Some(b)) => a.cmp(b),
+/// Sets the level of events inside matching spans, written as
+/// `target[span{field=value}]=level` like `EnvFilter` directives.
+///
+/// Field values containing `,`, `=`, `"`, `\\`, brackets or braces, or starting or ending with
+/// whitespace, are written in double quotes, with `"` and `\\` escaped by a backslash, e.g.
+/// `[flash{chip="a, b}"}]=trace`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpanRule {
+    /// Only events of this target and its submodules are affected, if set.
+    pub target: Option<String>,
+    /// The span name, an empty name matches all spans.
+    pub name: String,
+    /// Fields the span must have, with their formatted value if given.
+    pub fields: Vec<(String, Option<String>)>,
+    pub level: LogLevel,
+}
+
+impl SpanRule {
+    /// Creates a rule for the spans with the same name and field values as `span`.
+    pub fn exact(span: &Span, level: LogLevel) -> Self {
+        Self {
+            target: None,
+            name: span.name.clone(),
+            fields: span
+                .fields
+                .iter()
+                .map(|(name, value)| (name.clone(), Some(format_value(value))))
+                .collect(),
+            level,
+        }
+    }
+
+    fn matches(&self, target: &str, span: &Span) -> bool {
+        self.target
+            .as_deref()
+            .is_none_or(|module| is_within(target, module))
+            && (self.name.is_empty() || self.name == span.name)
+            && self.fields.iter().all(|(name, expected)| {
+                span.fields.get(name).is_some_and(|value| {
+                    expected
+                        .as_ref()
+                        .is_none_or(|expected| *expected == format_value(value))
+                })
             })
-            .expect("at least one filter element");
-        let filter = self.matrix.get(&target).or_else(|| self.matrix.get(&None));
+    }
+
+    /// Rules with a longer target and more fields win over other rules for the same span.
+    fn specificity(&self) -> (usize, usize) {
+        (
+            self.target.as_deref().map_or(0, str::len),
+            self.fields.len(),
+        )
+    }
+
+    fn parse(directive: &str) -> Result<Self, &'static str> {
+        let (target, rest) = directive.split_once('[').expect("a span directive");
+        let (span, level) = rest.rsplit_once(']').ok_or("missing `]`")?;
+        let level = match level.trim() {
+            "" => LogLevel::Trace,
+            level => level
+                .strip_prefix('=')
+                .and_then(|level| parse_directive_level(level.trim()))
+                .ok_or("unknown level")?,
+        };
+
+        let (name, fields) = match span.split_once('{') {
+            Some((name, fields)) => {
+                let fields = fields.strip_suffix('}').ok_or("missing `}`")?;
+                (name, parse_fields(fields)?)
+            }
+            None => (span, vec![]),
+        };
+
+        let target = target.trim();
+        if target.contains(char::is_whitespace) {
+            return Err("targets can't contain whitespace");
+        }
+        Ok(Self {
+            target: (!target.is_empty()).then(|| target.to_string()),
+            name: name.trim().to_string(),
+            fields,
+            level,
+        })
+    }
+}
+
+impl Display for SpanRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}[{}",
+            self.target.as_deref().unwrap_or_default(),
+            self.name
+        )?;
+        if !self.fields.is_empty() {
+            let fields = self
+                .fields
+                .iter()
+                .map(|(name, value)| match value {
+                    Some(value) => format!("{name}={}", quote_value(value)),
+                    None => name.clone(),
+                })
+                .collect::<Vec<_>>();
+            write!(f, "{{{}}}", fields.join(","))?;
+        }
+        write!(f, "]={}", directive_level(&self.level))
+    }
+}
+
+/// Parses the fields of a span rule, `field=value` or `field` separated by commas.
+fn parse_fields(fields: &str) -> Result<Vec<(String, Option<String>)>, &'static str> {
+    let mut parsed = vec![];
+    let mut rest = fields;
+    loop {
+        let end = rest.find([',', '=']).unwrap_or(rest.len());
+        let name = rest[..end].trim();
+        rest = &rest[end..];
+
+        let value = match rest.strip_prefix('=') {
+            Some(value) => {
+                let value = value.trim_start();
+                let (value, remainder) = match value.strip_prefix('"') {
+                    Some(quoted) => {
+                        let (value, remainder) = unquote(quoted)?;
+                        let remainder = remainder.trim_start();
+                        if !remainder.is_empty() && !remainder.starts_with(',') {
+                            return Err("unexpected text after a quoted value");
+                        }
+                        (value, remainder)
+                    }
+                    None => {
+                        let end = value.find(',').unwrap_or(value.len());
+                        let bare = value[..end].trim();
+                        if bare.contains(['"', '=', '\\', '[', ']', '{', '}']) {
+                            return Err("field values with special characters must be quoted");
+                        }
+                        (bare.to_string(), &value[end..])
+                    }
+                };
+                rest = remainder;
+                Some(value)
+            }
+            None => None,
+        };
 
-        if let Some(filter) = filter {
-            level >= filter
+        if name.is_empty() {
+            if value.is_some() {
+                return Err("missing field name");
+            }
         } else {
-            false
+            parsed.push((name.to_string(), value));
+        }
+        match rest.strip_prefix(',') {
+            Some(remainder) => rest = remainder,
+            None => return Ok(parsed),
         }
     }
+}
+
+/// Reads a quoted value up to its closing `"`, returning the value and the text after it.
+fn unquote(quoted: &str) -> Result<(String, &str), &'static str> {
+    let mut value = String::new();
+    let mut chars = quoted.char_indices();
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '"' => return Ok((value, &quoted[index + 1..])),
+            '\\' => match chars.next() {
+                Some((_, escaped @ ('"' | '\\'))) => value.push(escaped),
+                _ => return Err("invalid escape in a quoted value"),
+            },
+            c => value.push(c),
+        }
+    }
+    Err("missing closing `\"`")
+}
+
+/// Quotes a field value if it could not be parsed back as it is, see [`SpanRule`].
+fn quote_value(value: &str) -> Cow<'_, str> {
+    let needs_quotes =
+        value.contains([',', '=', '"', '\\', '[', ']', '{', '}']) || value.trim() != value;
+    if !needs_quotes {
+        return Cow::Borrowed(value);
+    }
+    let escaped = value.replace('\\', "\\\\").replace('"', "\\\"");
+    Cow::Owned(format!("\"{escaped}\""))
+}
+
+/// A module in the target hierarchy, with the level set for it, if any.
+#[derive(Debug, Clone, PartialEq, Default)]
+struct TrieNode {
+    level: Option<Levels>,
+    children: HashMap<String, TrieNode>,
+}
+
+impl TrieNode {
+    fn build(
+        matrix: &HashMap<Option<String>, LogLevel>,
+        sets: &HashMap<Option<String>, LevelSet>,
+    ) -> Self {
+        let mut trie = TrieNode::default();
+        for (target, level) in matrix {
+            let level = match sets.get(target) {
+                Some(set) => Levels::Only(*set),
+                None => Levels::AtLeast(*level),
+            };
+            match target {
+                Some(target) => trie.insert(target, level),
+                None => trie.level = Some(level),
+            }
+        }
+        trie
+    }
+
+    fn insert(&mut self, target: &str, level: Levels) {
+        let node = target.split("::").fold(self, |node, segment| {
+            node.children.entry(segment.to_string()).or_default()
+        });
+        node.level = Some(level);
+    }
+
+    /// Returns the levels of the longest path that `target` is within.
+    fn lookup(&self, target: &str) -> Option<Levels> {
+        let mut node = self;
+        let mut level = self.level;
+        for segment in target.split("::") {
+            match node.children.get(segment) {
+                Some(child) => node = child,
+                None => break,
+            }
+            level = node.level.or(level);
+        }
+        level
+    }
+}
+
+impl Default for LevelFilter {
+    /// Shows all events.
+    fn default() -> Self {
+        Self::new(HashMap::from([(None, LogLevel::Trace)]))
+    }
+}
+
+impl LevelFilter {
+    /// Returns `true` if events of `target` with `level` are shown.
+    ///
+    /// The most specific target of the filter wins, e.g. `probe_rs::probe` over `probe_rs`
+    /// for `probe_rs::probe::cmsisdap`, but not for `probe_rs::probe_foo`. Targets not covered
+    /// by the filter use the default level, and are hidden if there is none.
+    pub fn show(&self, target: &str, level: &LogLevel) -> bool {
+        self.trie
+            .lookup(target)
+            .is_some_and(|levels| levels.contains(level))
+    }
+
+    /// Returns `true` if `event` is shown.
+    ///
+    /// The innermost span of the event matched by a span rule decides, events outside of
+    /// such spans are filtered by their target like [`LevelFilter::show`] does.
+    pub fn show_event(&self, event: &Event) -> bool {
+        if !self.span_rules.is_empty() {
+            let rule = event
+                .span
+                .iter()
+                .chain(event.spans.iter().flatten().rev())
+                .find_map(|span| {
+                    self.span_rules
+                        .iter()
+                        .filter(|rule| rule.matches(&event.target, span))
+                        .max_by_key(|rule| rule.specificity())
+                });
+            if let Some(rule) = rule {
+                return event.level >= rule.level;
+            }
+        }
+        self.show(&event.target, &event.level)
+    }
+
+    /// Parses a comma separated list of directives in the `RUST_LOG` syntax, e.g.
+    /// `info,probe_rs=debug,jaylink=off`.
+    ///
+    /// A directive without a target sets the default level, a target without a level enables
+    /// all of its events and levels separated by `|`, e.g. `warn|trace`, show exactly those.
+    /// As with `RUST_LOG`, targets without a directive are hidden unless a default level is
+    /// given, but an empty string shows everything. Span rules are written as
+    /// `target[span{field=value}]=level`, where all but the brackets are optional.
+    pub fn from_directives(directives: &str) -> Result<Self, DirectiveError> {
+        let mut filter = Self::new(HashMap::from([(None, LogLevel::None)]));
+        let mut span_rules = vec![];
+        if directives.trim().is_empty() {
+            return Ok(Self::default());
+        }
+
+        for directive in split_directives(directives) {
+            if directive.is_empty() {
+                continue;
+            }
+            let error = |reason: &str| DirectiveError {
+                directive: directive.into(),
+                reason: reason.into(),
+            };
+
+            if directive.contains('[') {
+                span_rules.push(SpanRule::parse(directive).map_err(error)?);
+                continue;
+            }
+
+            let (target, levels) = match directive.split_once('=') {
+                Some((target, levels)) => {
+                    let levels = parse_directive_levels(levels.trim())
+                        .ok_or_else(|| error("unknown level"))?;
+                    (Some(target.trim()), levels)
+                }
+                None => match parse_directive_levels(directive) {
+                    Some(levels) => (None, levels),
+                    None => (Some(directive), Levels::AtLeast(LogLevel::Trace)),
+                },
+            };
+
+            if let Some(target) = target {
+                if target.is_empty() {
+                    return Err(error("missing target"));
+                }
+                if target.contains(char::is_whitespace) {
+                    return Err(error("targets can't contain whitespace"));
+                }
+            }
+            let target = target.map(String::from);
+            filter = match levels {
+                Levels::AtLeast(level) => filter.set_level(target, level),
+                Levels::Only(set) => filter.set_levels(target, set),
+            };
+        }
+
+        Ok(span_rules.into_iter().fold(filter, Self::add_span_rule))
+    }
 
     pub fn new(matrix: HashMap<Option<String>, LogLevel>) -> Self {
-        Self { matrix }
+        Self {
+            trie: TrieNode::build(&matrix, &HashMap::new()),
+            matrix,
+            sets: HashMap::new(),
+            span_rules: vec![],
+        }
     }
 
     pub fn matrix(&self) -> &HashMap<Option<String>, LogLevel> {
         &self.matrix
     }
 
+    /// Returns the levels shown for `target`, if the filter has an entry for it.
+    pub fn levels(&self, target: &Option<String>) -> Option<Levels> {
+        match self.sets.get(target) {
+            Some(set) => Some(Levels::Only(*set)),
+            None => self.matrix.get(target).copied().map(Levels::AtLeast),
+        }
+    }
+
+    pub fn span_rules(&self) -> &[SpanRule] {
+        &self.span_rules
+    }
+
+    /// Adds a span rule, replacing an earlier rule for the same spans.
+    pub fn add_span_rule(mut self, rule: SpanRule) -> Self {
+        self.span_rules.retain(|other| {
+            (&other.target, &other.name, &other.fields) != (&rule.target, &rule.name, &rule.fields)
+        });
+        self.span_rules.push(rule);
+        self
+    }
+
+    /// Shows `level` and all more severe levels of `target`.
     pub fn set_level(mut self, target: Option<String>, level: LogLevel) -> Self {
+        self.sets.remove(&target);
         let filter = self.matrix.entry(target).or_default();
         *filter = level;
-        Self {
-            matrix: self.matrix,
+        self.trie = TrieNode::build(&self.matrix, &self.sets);
+        self
+    }
+
+    /// Shows exactly the levels in `set` for `target`.
+    ///
+    /// Sets which are the same as a threshold are stored as one, so thresholds stay the default.
+    pub fn set_levels(mut self, target: Option<String>, set: LevelSet) -> Self {
+        let threshold = set.iter().next().unwrap_or(LogLevel::None);
+        if Levels::AtLeast(threshold).to_set() == set {
+            return self.set_level(target, threshold);
         }
+
+        self.matrix.insert(target.clone(), threshold);
+        self.sets.insert(target, set);
+        self.trie = TrieNode::build(&self.matrix, &self.sets);
+        self
     }
 
     pub fn remove(mut self, target: &Option<String>) -> Self {
         self.matrix.remove(target);
-        Self {
-            matrix: self.matrix,
+        self.sets.remove(target);
+        self.trie = TrieNode::build(&self.matrix, &self.sets);
+        self
+    }
+}
+
+/// Splits directives at commas, except for the ones separating span fields or inside quotes.
+fn split_directives(directives: &str) -> impl Iterator<Item = &str> {
+    let mut depth = 0usize;
+    let mut quoted = false;
+    let mut escaped = false;
+    directives
+        .split(move |c| {
+            if quoted {
+                match c {
+                    _ if escaped => escaped = false,
+                    '\\' => escaped = true,
+                    '"' => quoted = false,
+                    _ => {}
+                }
+                return false;
+            }
+            match c {
+                '"' => quoted = true,
+                '[' | '{' => depth += 1,
+                ']' | '}' => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+            c == ',' && depth == 0
+        })
+        .map(str::trim)
+}
+
+impl Display for LevelFilter {
+    /// Formats the filter as `RUST_LOG` directives, the default level first and span rules last.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut directives = self.matrix.iter().collect::<Vec<_>>();
+        directives.sort_by_key(|(target, _)| *target);
+
+        let directives = directives
+            .into_iter()
+            .map(|(target, level)| {
+                let level = match self.sets.get(target) {
+                    Some(set) => set
+                        .iter()
+                        .map(|level| directive_level(&level))
+                        .collect::<Vec<_>>()
+                        .join("|"),
+                    None => directive_level(level),
+                };
+                match target {
+                    Some(target) => format!("{target}={level}"),
+                    None => level,
+                }
+            })
+            .chain(self.span_rules.iter().map(SpanRule::to_string))
+            .collect::<Vec<_>>();
+        write!(f, "{}", directives.join(","))
+    }
+}
+
+fn directive_level(level: &LogLevel) -> String {
+    match level {
+        LogLevel::None => "off".to_string(),
+        level => level.to_string().to_ascii_lowercase(),
+    }
+}
+
+/// Parses the levels of a directive, either a threshold or a set like `warn|trace`.
+fn parse_directive_levels(levels: &str) -> Option<Levels> {
+    if !levels.contains('|') {
+        return parse_directive_level(levels).map(Levels::AtLeast);
+    }
+
+    let mut set = LevelSet::default();
+    for level in levels.split('|') {
+        match parse_directive_level(level.trim())? {
+            LogLevel::None => return None,
+            level => set.insert(level),
         }
     }
+    Some(Levels::Only(set))
+}
+
+/// Parses a level of a directive, `off` hides all events.
+fn parse_directive_level(level: &str) -> Option<LogLevel> {
+    if level.eq_ignore_ascii_case("off") {
+        Some(LogLevel::None)
+    } else {
+        LogLevel::from_str(level).ok()
+    }
+}
+
+/// A directive that could not be parsed by [`LevelFilter::from_directives`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct DirectiveError {
+    pub directive: String,
+    pub reason: String,
+}
+
+impl Display for DirectiveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.directive, self.reason)
+    }
+}
+
+impl std::error::Error for DirectiveError {}
+
+/// Returns `true` if `target` is `module` or one of its submodules.
+pub fn is_within(target: &str, module: &str) -> bool {
+    target
+        .strip_prefix(module)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
 }
@@ -1,6 +1,10 @@
-use log_viewer::{level_filter::LevelFilter, proto::log_level::LogLevel};
-use web_sys::MouseEvent;
-use yew::{classes, function_component, html, use_state, Callback, Html, Properties};
+use log_viewer::{
+    level_filter::{LevelFilter, Levels},
+    proto::log_level::LogLevel,
+};
+use wasm_bindgen::JsCast;
+use web_sys::{HtmlInputElement, MouseEvent};
+use yew::{classes, function_component, html, use_state, Callback, Event, Html, Properties};
 
 #[derive(Clone, PartialEq, Properties)]
 pub struct LevelPickerProps {
@@ -20,8 +24,65 @@ pub fn level_picker(props: &LevelPickerProps) -> Html {
         }
     };
 
-    html! {<div class="flex">
-        { for props.level_filter.matrix().iter().map(|(target, level)| {
+    let ontoggle = |target: Option<String>, level: LogLevel| {
+        let level_filter = props.level_filter.clone();
+        let on_select = props.on_select.clone();
+        move |event: MouseEvent| {
+            event.stop_propagation();
+            let mut set = level_filter
+                .levels(&target)
+                .map(Levels::to_set)
+                .unwrap_or_default();
+            if set.contains(&level) {
+                set.remove(&level);
+            } else {
+                set.insert(level);
+            }
+            on_select.emit(level_filter.clone().set_levels(target.clone(), set))
+        }
+    };
+
+    let directive_error = use_state(|| None);
+    let onchange = {
+        let on_select = props.on_select.clone();
+        let directive_error = directive_error.clone();
+        move |event: Event| {
+            // When events are created the target is undefined, it's only
+            // when dispatched does the target get added.
+            let target = event.target();
+            // Events can bubble so this listener might catch events from child
+            // elements which are not of type HtmlInputElement
+            let input = target
+                .and_then(|t| t.dyn_into::<HtmlInputElement>().ok())
+                .unwrap();
+            match LevelFilter::from_directives(&input.value()) {
+                Ok(level_filter) => {
+                    directive_error.set(None);
+                    on_select.emit(level_filter);
+                }
+                Err(error) => directive_error.set(Some(error.to_string())),
+            }
+        }
+    };
+
+    // The default first, then the targets in a stable order.
+    let mut entries = props.level_filter.matrix().iter().collect::<Vec<_>>();
+    entries.sort_by_key(|(target, _)| *target);
+
+    html! {<div class="flex flex-wrap items-center">
+        <input
+            {onchange}
+            value={props.level_filter.to_string()}
+            placeholder="RUST_LOG directives, e.g. info,probe_rs=debug"
+            title="RUST_LOG directives, e.g. info,probe_rs=debug,jaylink=off"
+            class={classes!["ml-3", "my-3", "px-2", "py-1", "border", "font-mono", if directive_error.is_some() { "border-red-500" } else { "border-black" }]}
+        />
+        {if let Some(error) = &*directive_error {
+            html!{<span class={classes!["ml-2", "text-red-500"]}>{error}</span>}
+        } else {
+            html!{}
+        }}
+        { for entries.into_iter().map(|(target, level)| {
             let color = level.color();
             let open = open.clone();
             let target_string = target.clone().unwrap_or_else(|| "default".into());
@@ -80,12 +141,21 @@ pub fn level_picker(props: &LevelPickerProps) -> Html {
                         LogLevel::Debug,
                         LogLevel::Trace,
                     ].into_iter().map(|level| {
-                        let checked = props.level_filter.matrix().get(&target) == Some(&level);
+                        let levels = props.level_filter.levels(&target);
                         let color = level.color();
-                        html!{<button onclick={onselect(target.clone(), level)} class={classes!["my-3", "px-2", "py-1", "border-r", "border-t", "border-b", "border-black", format!("bg-{color}")]}>
-                            <label class="pr-2">{format!("{level}")}</label>
-                            <input type="checkbox" {checked} />
-                        </button>}
+                        // The label selects the level and everything more severe, the checkbox
+                        // toggles just this level.
+                        let checkbox = if level == LogLevel::None {
+                            html!{}
+                        } else {
+                            let checked = levels.is_some_and(|levels| levels.contains(&level));
+                            html!{<input type="checkbox" {checked} onclick={ontoggle(target.clone(), level)} />}
+                        };
+                        let selected = levels == Some(Levels::AtLeast(level));
+                        html!{<span class={classes!["my-3", "px-2", "py-1", "border-r", "border-t", "border-b", "border-black", format!("bg-{color}")]}>
+                            <button onclick={onselect(target.clone(), level)} class={classes!["pr-2", selected.then_some("font-bold")]}>{format!("{level}")}</button>
+                            {checkbox}
+                        </span>}
                     }).collect::<Html>()
                 }
                 </ul>
@@ -1,3 +1,19 @@
+pub mod annotations;
+pub mod bookmarks;
+pub mod context;
+pub mod expansion;
+pub mod facets;
+pub mod fuzzy;
+pub mod gist_files;
 pub mod level_filter;
+pub mod navigation;
+pub mod permalink;
 pub mod proto;
+pub mod query;
+pub mod rows;
+pub mod search;
 pub mod state;
+pub mod targets;
+pub mod timeline;
+pub mod timestamp;
+pub mod view_state;
@@ -0,0 +1,178 @@
+use gloo::net::http::{Request, RequestBuilder};
+use log_viewer::annotations::{Annotation, Annotations, ANNOTATIONS_FILE};
+use web_sys::RequestCache;
+
+use crate::{
+    cache::{cache_key, CachedLog, LogCache},
+    error::LoadError,
+    gh_token,
+    gist::{Gist, Progress},
+    GH_API_VERSION,
+};
+
+/// Loads the log stored in a gist, going through the IndexedDB cache.
+///
+/// Pinned revisions never change and are served from the cache directly, the latest
+/// revision is revalidated with the `ETag` of the cached response. Annotations are published
+/// to the latest revision, so pinned revisions always load them from there.
+pub async fn load_gist(
+    id: &str,
+    revision: Option<&str>,
+    progress: &dyn Fn(Progress),
+) -> Result<CachedLog, LoadError> {
+    let key = cache_key(id, revision);
+    let cache = LogCache::open()
+        .await
+        .inspect_err(|error| log::warn!("Not using the log cache: {error}"))
+        .ok();
+    let cached = match &cache {
+        Some(cache) => cache
+            .get(&key)
+            .await
+            .inspect_err(|error| log::warn!("Failed to read {key} from the cache: {error}"))
+            .ok()
+            .flatten(),
+        None => None,
+    };
+
+    if let (Some(cached), Some(_)) = (&cached, revision) {
+        log::debug!("Using cached revision {key}");
+        return Ok(with_latest_annotations(id, cached.clone()).await);
+    }
+
+    log::debug!("Loading gist {} at revision {:?}", id, revision);
+    let url = match revision {
+        Some(revision) => format!("https://api.github.com/gists/{id}/{revision}"),
+        None => format!("https://api.github.com/gists/{id}"),
+    };
+    let mut request = authorize(Request::get(&url));
+    if let Some(etag) = cached.as_ref().and_then(|cached| cached.etag.as_deref()) {
+        request = request.header("If-None-Match", etag);
+    }
+
+    let response = match request.send().await {
+        Ok(response) => response,
+        Err(error) => {
+            if let Some(cached) = cached {
+                log::warn!("Failed to revalidate {key}, using cached copy: {error}");
+                return Ok(cached);
+            }
+            return Err(LoadError::network(error));
+        }
+    };
+
+    match (response.status(), cached) {
+        (304, Some(cached)) => {
+            log::debug!("Cached copy of {key} is up to date");
+            return Ok(cached);
+        }
+        (200, _) => {}
+        _ => return Err(LoadError::http(&response)),
+    }
+
+    let etag = response.headers().get("etag");
+    let gist: Gist = response.json().await.map_err(LoadError::network)?;
+    let mut log = CachedLog {
+        gist: gist.info(),
+        etag,
+        content: gist.load_log(progress).await?,
+        // Broken annotations should not keep anyone from reading the log.
+        annotations: gist
+            .annotations()
+            .await
+            .inspect_err(|error| log::warn!("Ignoring the annotations of {key}: {error}"))
+            .unwrap_or_default(),
+    };
+    if revision.is_some() {
+        log = with_latest_annotations(id, log).await;
+    }
+
+    if let Some(cache) = &cache {
+        if let Err(error) = cache.put(&key, &log).await {
+            log::warn!("Failed to cache {key}: {error}");
+        }
+    }
+
+    Ok(log)
+}
+
+/// Replaces the annotations of `log` with the ones of the latest revision of gist `id`.
+///
+/// Keeps the annotations `log` has if the latest ones can't be loaded.
+async fn with_latest_annotations(id: &str, mut log: CachedLog) -> CachedLog {
+    let latest = async {
+        let response = authorize(Request::get(&format!("https://api.github.com/gists/{id}")))
+            .cache(RequestCache::NoCache)
+            .send()
+            .await
+            .map_err(LoadError::network)?;
+        if !response.ok() {
+            return Err(LoadError::http(&response));
+        }
+        let gist: Gist = response.json().await.map_err(LoadError::network)?;
+        gist.annotations().await
+    };
+    match latest.await {
+        Ok(annotations) => log.annotations = annotations,
+        Err(error) => log::warn!("Failed to load the latest annotations of gist {id}: {error}"),
+    }
+    log
+}
+
+/// Adds the API version and, if there is one, the token to a Github API request.
+fn authorize(mut request: RequestBuilder) -> RequestBuilder {
+    request = request.header("X-GitHub-Api-Version", GH_API_VERSION);
+    if let Some(token) = gh_token() {
+        request = request.header("Authorization", &format!("Bearer {token}"));
+    }
+    request
+}
+
+/// Stores a freshly uploaded log, so opening its link does not download it again.
+pub async fn cache_upload(log: &CachedLog) {
+    let key = cache_key(&log.gist.id, None);
+    match LogCache::open().await {
+        Ok(cache) => {
+            if let Err(error) = cache.put(&key, log).await {
+                log::warn!("Failed to cache {key}: {error}");
+            }
+        }
+        Err(error) => log::warn!("Not using the log cache: {error}"),
+    }
+}
+
+/// Adds `annotation` to the annotations file of gist `id`.
+///
+/// The latest annotations are fetched first, so annotations published by others since the
+/// log was loaded are kept. Returns all annotations.
+pub async fn publish_annotation(
+    id: &str,
+    annotation: Annotation,
+) -> Result<Annotations, LoadError> {
+    let url = format!("https://api.github.com/gists/{id}");
+
+    let response = authorize(Request::get(&url))
+        .cache(RequestCache::NoCache)
+        .send()
+        .await
+        .map_err(LoadError::network)?;
+    if !response.ok() {
+        return Err(LoadError::http(&response));
+    }
+    let gist: Gist = response.json().await.map_err(LoadError::network)?;
+    let published = gist.published_annotations().await?;
+    let (annotations, update) = Annotations::publish(published.as_deref(), annotation)
+        .map_err(|error| LoadError::Corrupt(format!("{ANNOTATIONS_FILE}: {error}")))?;
+
+    let response = authorize(Request::patch(&url))
+        .json(&update)
+        .map_err(|e| LoadError::Encode(e.to_string()))?
+        .send()
+        .await
+        .map_err(LoadError::network)?;
+    if !response.ok() {
+        return Err(LoadError::http(&response));
+    }
+
+    Ok(annotations)
+}
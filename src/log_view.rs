@@ -0,0 +1,195 @@
+use std::{collections::HashMap, rc::Rc};
+
+use gloo::events::EventListener;
+use log_viewer::{
+    annotations::Annotations,
+    bookmarks::Bookmarks,
+    context::Visibility,
+    rows::{flatten, Row},
+    state::State,
+    view_state::ViewState,
+};
+use web_sys::Element;
+use yew::{
+    function_component, html, use_effect_with, use_memo, use_mut_ref, use_node_ref, use_state_eq,
+    Callback, Html, NodeRef, Properties, UseStateHandle,
+};
+
+use crate::info_node::{InfoNode, ROW_HEIGHT};
+
+/// Rows rendered above and below the viewport, so scrolling does not reveal blank space.
+const OVERSCAN: usize = 20;
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct LogViewProps {
+    pub(crate) state: Rc<State>,
+    pub(crate) view_state: UseStateHandle<ViewState>,
+    pub(crate) visibility: Rc<Visibility>,
+    pub(crate) on_open: Callback<usize>,
+    pub(crate) bookmarks: Rc<Bookmarks>,
+    pub(crate) on_bookmark: Callback<usize>,
+    pub(crate) annotations: Rc<Annotations>,
+    pub(crate) on_annotate: Callback<usize>,
+    /// Event to bring into the middle of the viewport.
+    pub(crate) scroll_to: Option<usize>,
+}
+
+/// The rows that fit into the viewport.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+struct Viewport {
+    first: usize,
+    count: usize,
+}
+
+/// Top of the list relative to the viewport in pixels, negative once scrolled past it.
+fn list_top(list: &NodeRef) -> Option<f64> {
+    list.cast::<Element>()
+        .map(|list| list.get_bounding_client_rect().top())
+}
+
+fn viewport_height() -> f64 {
+    gloo::utils::window()
+        .inner_height()
+        .ok()
+        .and_then(|height| height.as_f64())
+        .unwrap_or_default()
+}
+
+fn measure(list: &NodeRef) -> Viewport {
+    let top = list_top(list).unwrap_or_default();
+    Viewport {
+        first: (-top / ROW_HEIGHT).max(0.0) as usize,
+        count: (viewport_height() / ROW_HEIGHT).ceil() as usize + 1,
+    }
+}
+
+fn key(row: &Row) -> String {
+    match *row {
+        Row::Gap { event, .. } => format!("gap-{event}"),
+        Row::Message { event, .. } | Row::Span { event, .. } => event.to_string(),
+        Row::Annotation { event, number, .. } => format!("{event}-{number}"),
+    }
+}
+
+/// The event tree as a flat list, only the rows in and around the viewport are rendered.
+///
+/// The page keeps scrolling as a whole, the list reserves the height of all rows and places
+/// the rendered ones where they belong.
+#[function_component(LogView)]
+pub fn log_view(props: &LogViewProps) -> Html {
+    let list = use_node_ref();
+    let viewport = use_state_eq(Viewport::default);
+    let rows = use_memo(
+        (
+            Rc::as_ptr(&props.state) as usize,
+            Rc::as_ptr(&props.visibility) as usize,
+            Rc::as_ptr(&props.annotations) as usize,
+            props.view_state.expanded.clone(),
+            props.view_state.time_range,
+        ),
+        {
+            let state = props.state.clone();
+            let view_state = (*props.view_state).clone();
+            let visibility = props.visibility.clone();
+            let annotations = props.annotations.clone();
+            move |_| flatten(&state, &view_state, &visibility, &annotations)
+        },
+    );
+
+    {
+        let list = list.clone();
+        let viewport = viewport.clone();
+        use_effect_with((), move |_| {
+            viewport.set(measure(&list));
+            let listeners = ["scroll", "resize"].map(|name| {
+                let list = list.clone();
+                let viewport = viewport.clone();
+                EventListener::new(&gloo::utils::window(), name, move |_| {
+                    viewport.set(measure(&list))
+                })
+            });
+            move || drop(listeners)
+        });
+    }
+
+    // The first row in the viewport, as an index into the rows it was found in, and how far the
+    // row is from the top of the viewport, kept in place when rows above it appear or disappear.
+    let anchor = use_mut_ref(|| None::<(usize, f64)>);
+    let last_rows = use_mut_ref(|| None::<Rc<Vec<Row>>>);
+    let scrolled_to = use_mut_ref(|| None);
+    {
+        let list = list.clone();
+        let rows = rows.clone();
+        use_effect_with(
+            (Rc::as_ptr(&rows) as usize, props.scroll_to, *viewport),
+            move |(_, scroll_to, _)| {
+                let Some(top) = list_top(&list) else {
+                    return;
+                };
+                let scroll_by =
+                    |offset: f64| gloo::utils::window().scroll_by_with_x_and_y(0.0, offset);
+                let last = last_rows.replace(Some(rows.clone()));
+
+                if scrolled_to.replace(*scroll_to) != *scroll_to {
+                    let index = scroll_to.and_then(|event| {
+                        rows.iter().position(|row| row.selectable() == Some(event))
+                    });
+                    if let Some(index) = index {
+                        let middle = (viewport_height() - ROW_HEIGHT) / 2.0;
+                        scroll_by(top + index as f64 * ROW_HEIGHT - middle);
+                        *anchor.borrow_mut() = Some((index, middle));
+                        return;
+                    }
+                }
+                let last_anchor = *anchor.borrow();
+                if let (Some(last), Some((first, offset))) = (last, last_anchor) {
+                    if !Rc::ptr_eq(&last, &rows) {
+                        // Rows are not in event order, so the anchor or the first row after it
+                        // which is still there is looked up by its key.
+                        let index = rows
+                            .iter()
+                            .enumerate()
+                            .map(|(index, row)| (key(row), index))
+                            .collect::<HashMap<_, _>>();
+                        let found = last
+                            .get(first..)
+                            .unwrap_or_default()
+                            .iter()
+                            .find_map(|row| index.get(&key(row)));
+                        if let Some(&index) = found {
+                            scroll_by(top + index as f64 * ROW_HEIGHT - offset);
+                            *anchor.borrow_mut() = Some((index, offset));
+                            return;
+                        }
+                    }
+                }
+
+                let first = (-top / ROW_HEIGHT).max(0.0) as usize;
+                *anchor.borrow_mut() =
+                    (first < rows.len()).then_some((first, top + first as f64 * ROW_HEIGHT));
+            },
+        );
+    }
+
+    let start = viewport.first.saturating_sub(OVERSCAN).min(rows.len());
+    let end = (viewport.first + viewport.count + OVERSCAN).min(rows.len());
+
+    html! {<div ref={list} style={format!("height: {}px", rows.len() as f64 * ROW_HEIGHT)}>
+        <div style={format!("transform: translateY({}px)", start as f64 * ROW_HEIGHT)}>
+            {for rows[start..end].iter().map(|row| html!{
+                <InfoNode
+                    key={key(row)}
+                    row={*row}
+                    state={props.state.clone()}
+                    view_state={props.view_state.clone()}
+                    visibility={props.visibility.clone()}
+                    on_open={props.on_open.clone()}
+                    bookmarks={props.bookmarks.clone()}
+                    on_bookmark={props.on_bookmark.clone()}
+                    annotations={props.annotations.clone()}
+                    on_annotate={props.on_annotate.clone()}
+                />
+            })}
+        </div>
+    </div>}
+}
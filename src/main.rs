@@ -1,32 +1,85 @@
+mod actions;
+mod bookmark_panel;
+mod cache;
+mod clipboard;
+mod command_palette;
 mod context_menu;
+mod detail_panel;
+mod download;
+mod error;
+mod expand_bar;
+mod facet_panel;
 mod gist;
 mod info_node;
 mod level_picker;
+mod loader;
+mod log_view;
 mod pill;
-
-use std::{
-    collections::{BTreeMap, HashMap},
-    rc::Rc,
-    str::FromStr,
-};
+mod query_bar;
+mod recent_logs;
+mod revision_picker;
+mod search_bar;
+mod shortcuts;
+mod status;
+mod target_sidebar;
+mod text_filters;
+mod theme;
+mod timeline_bar;
+
+use std::rc::Rc;
 
 use gloo::{
+    events::EventListener,
     history::{BrowserHistory, History},
     net::http::Request,
+    storage::{LocalStorage, Storage},
+    timers::future::TimeoutFuture,
 };
 use wasm_bindgen::JsCast;
-use web_sys::HtmlTextAreaElement;
+use web_sys::{HtmlElement, HtmlInputElement, HtmlTextAreaElement};
 use yew::prelude::*;
 
 use crate::{
+    actions::{registry, Action},
+    bookmark_panel::BookmarkPanel,
+    cache::{add_recent_log, load_bookmarks, store_bookmarks, CachedLog},
+    clipboard::copy_text,
+    command_palette::CommandPalette,
     context_menu::{ContextMenu, ContextMenuProvider},
-    gist::{CreateGist, CreateGistFile, Gist},
-    info_node::InfoNode,
+    detail_panel::DetailPanel,
+    download::download,
+    error::LoadError,
+    expand_bar::ExpandBar,
+    facet_panel::FacetPanel,
+    gist::{Gist, GistInfo, Progress},
     level_picker::LevelPicker,
-    // level_picker::LevelPicker,
+    loader::{cache_upload, load_gist, publish_annotation},
+    log_view::LogView,
+    query_bar::QueryBar,
+    recent_logs::RecentLogs,
+    revision_picker::RevisionPicker,
+    search_bar::SearchBar,
+    shortcuts::Shortcuts,
+    status::{ErrorView, LoadingView},
+    target_sidebar::TargetSidebar,
+    text_filters::TextFilters,
+    theme::{apply_theme, toggle_theme},
+    timeline_bar::Timeline,
 };
 
-use log_viewer::{level_filter::LevelFilter, proto::log_level::LogLevel, state::State};
+use log_viewer::{
+    annotations::{Annotation, Annotations},
+    bookmarks::{log_identity, Bookmarks},
+    context::Visibility,
+    gist_files::CreateGist,
+    level_filter::LevelFilter,
+    navigation::find_span,
+    permalink::parse_fragment,
+    search::find_matches,
+    state::State,
+    targets::TargetNode,
+    view_state::{Source, ViewState},
+};
 
 fn gh_token() -> Option<&'static str> {
     std::option_env!("GH_TOKEN")
@@ -42,101 +95,439 @@ fn main() {
 
 #[function_component]
 fn App() -> Html {
-    let level_filter = use_state(|| {
-        let mut map = HashMap::new();
+    let url_error = use_state(|| view_state_from_url().err());
+    // Identity of the log whose skipped lines notice was dismissed.
+    let skipped_dismissed = use_state(String::new);
+    let view_state = use_state(|| view_state_from_url().unwrap_or_default());
+
+    use_effect_with(view_state.clone(), |view_state| {
         let history = BrowserHistory::new();
         let location = history.location();
-        let query = location.query::<HashMap<String, String>>().unwrap();
-        for (key, value) in query {
-            if key.starts_with("filter") {
-                let key = key.replace('-', "::").replace("filter::", "");
-                map.insert(
-                    if key != "filter" { Some(key) } else { None },
-                    LogLevel::from_str(&value).unwrap(),
-                );
+        // Keep a permalink in the fragment, so reloading jumps to the event again.
+        history.replace(format!(
+            "{}?{}{}",
+            location.path(),
+            view_state.to_query(),
+            location.hash()
+        ));
+    });
+
+    let log = use_state(|| LogStatus::Loading);
+    let show_upload = use_state(|| false);
+    let upload_value = use_state(String::new);
+    let progress = use_state(|| None);
+    let scroll_to = use_state(|| None);
+    let show_facets = use_state(|| false);
+    let show_targets = use_state(|| false);
+    let show_palette = use_state(|| false);
+    let show_details = use_state(|| false);
+    let show_bookmarks = use_state(|| false);
+    let bookmarks = use_state(|| Rc::new(Bookmarks::default()));
+
+    let loaded = match &*log {
+        LogStatus::Loaded { state, .. } => Some(state.clone()),
+        _ => None,
+    };
+    let identity = match &*log {
+        LogStatus::Loaded { identity, .. } => Some(identity.clone()),
+        _ => None,
+    };
+    {
+        // Event indices are specific to a log.
+        let bookmarks = bookmarks.clone();
+        use_effect_with(identity.clone(), move |identity| {
+            let loaded = identity.as_deref().map(load_bookmarks);
+            bookmarks.set(Rc::new(loaded.unwrap_or_default()));
+        });
+    }
+    let on_bookmarks = {
+        let bookmarks = bookmarks.clone();
+        let identity = identity.clone();
+        Callback::from(move |new_bookmarks: Bookmarks| {
+            if let Some(identity) = &identity {
+                store_bookmarks(identity, &new_bookmarks);
+            }
+            bookmarks.set(Rc::new(new_bookmarks));
+        })
+    };
+    let matches = use_memo(
+        (
+            loaded.as_ref().map(|state| Rc::as_ptr(state) as usize),
+            view_state.search.clone(),
+            view_state.filter.clone(),
+            view_state.text_filters.clone(),
+            view_state.field_filters.clone(),
+            view_state.query.clone(),
+            view_state.time_range,
+        ),
+        {
+            let loaded = loaded.clone();
+            let view_state = view_state.clone();
+            move |_| match loaded.map(|state| find_matches(&state, &view_state)) {
+                Some(Ok(matches)) => (Rc::new(matches), None),
+                Some(Err(error)) => (Rc::default(), Some(error.to_string())),
+                None => (Rc::default(), None),
+            }
+        },
+    );
+    let (matches, search_error) = (*matches).clone();
+    let visibility = use_memo(
+        (
+            loaded.as_ref().map(|state| Rc::as_ptr(state) as usize),
+            view_state.filter.clone(),
+            view_state.text_filters.clone(),
+            view_state.field_filters.clone(),
+            view_state.query.clone(),
+            view_state.time_range,
+            view_state.context,
+            view_state.revealed.clone(),
+        ),
+        {
+            let loaded = loaded.clone();
+            let view_state = view_state.clone();
+            move |_| {
+                loaded
+                    .map(|state| Visibility::new(&state, &view_state))
+                    .unwrap_or_else(|| Visibility {
+                        rows: vec![],
+                        gaps: Default::default(),
+                    })
             }
+        },
+    );
+    let targets = use_memo(
+        (
+            loaded.as_ref().map(|state| Rc::as_ptr(state) as usize),
+            visibility.clone(),
+            *show_targets,
+        ),
+        {
+            let loaded = loaded.clone();
+            let visibility = visibility.clone();
+            let show_targets = *show_targets;
+            // Only built while the sidebar is open, it walks all events.
+            move |_| {
+                loaded
+                    .filter(|_| show_targets)
+                    .map(|state| TargetNode::new(&state, &visibility))
+            }
+        },
+    );
+    let current_match = view_state
+        .selected
+        .and_then(|selected| matches.iter().position(|&m| m == selected));
+
+    let on_input = {
+        let view_state = view_state.clone();
+        move |search| {
+            view_state.set(ViewState {
+                search,
+                ..(*view_state).clone()
+            })
         }
-        map.insert(None, LogLevel::Trace);
-        LevelFilter::new(map)
-    });
+    };
 
-    use_effect_with(level_filter.clone(), |level_filter| {
-        let history = BrowserHistory::new();
-        let location = history.location();
+    let step_match = |forward: bool| {
+        let view_state = view_state.clone();
+        let matches = matches.clone();
+        let loaded = loaded.clone();
+        let scroll_to = scroll_to.clone();
+        move |_| {
+            let Some(state) = &loaded else {
+                return;
+            };
+            if matches.is_empty() {
+                return;
+            }
+            // Rows are in event order, so the neighbouring match is found by index.
+            let selected = view_state.selected;
+            let target = if forward {
+                matches
+                    .iter()
+                    .find(|&&m| selected.is_none_or(|s| m > s))
+                    .unwrap_or(&matches[0])
+            } else {
+                matches
+                    .iter()
+                    .rev()
+                    .find(|&&m| selected.is_none_or(|s| m < s))
+                    .unwrap_or(&matches[matches.len() - 1])
+            };
 
-        history
-            .push_with_query(location.path(), {
-                let mut map = location.query::<HashMap<String, String>>().unwrap();
-                let mut remove_keys = vec![];
-                for key in map.keys() {
-                    if key.starts_with("filter") {
-                        remove_keys.push(key.clone());
+            let mut expanded = view_state.expanded.clone();
+            expanded.extend(state.ancestors(*target));
+            view_state.set(ViewState {
+                expanded,
+                selected: Some(*target),
+                ..(*view_state).clone()
+            });
+            scroll_to.set(Some(*target));
+        }
+    };
+    let on_previous = step_match(false);
+    let on_next = step_match(true);
+
+    // Annotations published since the log was loaded, with the id of their gist.
+    let published = use_state(|| None::<(String, Rc<Annotations>)>);
+    let on_annotate = {
+        let log = log.clone();
+        let published = published.clone();
+        Callback::from(move |event| {
+            let LogStatus::Loaded { source, .. } = &*log else {
+                return;
+            };
+            let Some(comment) = gloo::dialogs::prompt("Annotation", None) else {
+                return;
+            };
+            if comment.trim().is_empty() {
+                return;
+            }
+            let Some(author) = annotation_author() else {
+                return;
+            };
+            let annotation = Annotation {
+                event,
+                comment,
+                author,
+                created_at: String::from(js_sys::Date::new_0().to_iso_string()),
+            };
+            let published = published.clone();
+            let gist = source.gist.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match publish_annotation(&gist, annotation).await {
+                    Ok(annotations) => published.set(Some((gist, Rc::new(annotations)))),
+                    Err(error) => {
+                        log::warn!("Failed to publish the annotation: {error}");
+                        gloo::dialogs::alert(&format!(
+                            "The annotation could not be published: {error}"
+                        ));
                     }
                 }
-                for key in remove_keys {
-                    map.remove(&key);
-                }
-                for (target, filter) in level_filter.matrix() {
-                    map.insert(
-                        format!(
-                            "filter{}",
-                            if let Some(target) = target {
-                                format!("::{target}")
-                            } else {
-                                "".into()
-                            }
-                        )
-                        .replace("::", "-"),
-                        filter.to_string(),
+            });
+        })
+    };
+    let on_action = {
+        let view_state = view_state.clone();
+        let matches = matches.clone();
+        let visibility = visibility.clone();
+        let loaded = loaded.clone();
+        let scroll_to = scroll_to.clone();
+        let show_upload = show_upload.clone();
+        let show_targets = show_targets.clone();
+        let show_facets = show_facets.clone();
+        let show_palette = show_palette.clone();
+        let show_details = show_details.clone();
+        let show_bookmarks = show_bookmarks.clone();
+        let bookmarks = bookmarks.clone();
+        let on_bookmarks = on_bookmarks.clone();
+        let on_annotate = on_annotate.clone();
+        Callback::from(move |action| match action {
+            Action::Load => {
+                let Some(input) = gloo::dialogs::prompt("Gist URL or id", None) else {
+                    return;
+                };
+                // Accept both https://gist.github.com/<user>/<id> and a bare id.
+                let path = input.trim().split(['?', '#']).next().unwrap_or_default();
+                let Some(gist) = path.split('/').rfind(|segment| !segment.is_empty()) else {
+                    return;
+                };
+                set_source(
+                    &view_state,
+                    ViewState {
+                        source: Some(Source {
+                            gist: gist.into(),
+                            revision: None,
+                        }),
+                        filter: view_state.filter.clone(),
+                        time_mode: view_state.time_mode,
+                        ..Default::default()
+                    },
+                );
+            }
+            Action::Create => show_upload.set(true),
+            Action::Share => match gloo::utils::window().location().href() {
+                Ok(href) => copy_text(&href),
+                Err(error) => log::warn!("Failed to get the link: {error:?}"),
+            },
+            Action::Export => {
+                if let Some(state) = &loaded {
+                    let name = view_state
+                        .source
+                        .as_ref()
+                        .map(|source| source.gist.as_str())
+                        .unwrap_or("log");
+                    download(
+                        &format!("{name}.jsonl"),
+                        "application/x-ndjson",
+                        &visibility.export(state),
                     );
                 }
-                map
-            })
-            .unwrap();
-    });
+            }
+            Action::FilterPreset(directives) => match LevelFilter::from_directives(directives) {
+                Ok(filter) => view_state.set(ViewState {
+                    filter,
+                    ..(*view_state).clone()
+                }),
+                Err(error) => log::warn!("Invalid filter preset: {error}"),
+            },
+            Action::Expand(expansion) => {
+                let Some(state) = &loaded else {
+                    return;
+                };
+                view_state.set(ViewState {
+                    expanded: expansion.apply(state, &visibility, &matches, &view_state.expanded),
+                    ..(*view_state).clone()
+                })
+            }
+            Action::Navigate(navigation) => {
+                let Some(state) = &loaded else {
+                    return;
+                };
+                let new_state = navigation.apply(state, &view_state, &visibility);
+                scroll_to.set(new_state.selected);
+                view_state.set(new_state);
+            }
+            Action::JumpToSpan(name) => {
+                let Some(state) = &loaded else {
+                    return;
+                };
+                let Some(span) = find_span(state, view_state.selected, &name) else {
+                    return;
+                };
+                let mut expanded = view_state.expanded.clone();
+                expanded.extend(state.ancestors(span));
+                view_state.set(ViewState {
+                    expanded,
+                    selected: Some(span),
+                    ..(*view_state).clone()
+                });
+                scroll_to.set(Some(span));
+            }
+            Action::FocusSearch => {
+                if let Some(input) = gloo::utils::document()
+                    .get_element_by_id("search")
+                    .and_then(|element| element.dyn_into::<HtmlElement>().ok())
+                {
+                    let _ = input.focus();
+                }
+            }
+            Action::ToggleTimeMode => view_state.set(ViewState {
+                time_mode: view_state.time_mode.next(),
+                ..(*view_state).clone()
+            }),
+            Action::ToggleTargets => show_targets.set(!*show_targets),
+            Action::ToggleFacets => show_facets.set(!*show_facets),
+            Action::ToggleTheme => toggle_theme(),
+            Action::OpenPalette => show_palette.set(true),
+            Action::OpenDetails => show_details.set(view_state.selected.is_some()),
+            Action::ToggleBookmark => {
+                if let Some(selected) = view_state.selected {
+                    on_bookmarks.emit((**bookmarks).clone().toggle(selected));
+                }
+            }
+            Action::ToggleBookmarks => show_bookmarks.set(!*show_bookmarks),
+            Action::Annotate => {
+                if let Some(selected) = view_state.selected {
+                    on_annotate.emit(selected);
+                }
+            }
+        })
+    };
 
-    let gist = use_state(|| Err(anyhow::anyhow!("Loading file ...")));
-    let state = use_state(|| None);
-    let show_upload = use_state(|| false);
-    let upload_value = use_state(String::new);
-    // let selected_occurrence = use_state(|| 0);
-    // let total_occurrences = use_state(|| 0);
-    // let changed_occurrence = use_state(|| false);
-    // let search_value = use_state(String::new);
-    // let onclick_previous = {
-    //     let selected_occurrence = selected_occurrence.clone();
-    //     let changed_occurrence = changed_occurrence.clone();
-    //     let total_occurrences = total_occurrences.clone();
-    //     move |_| {
-    //         let value = *selected_occurrence - 1;
-    //         let value = value.clamp(0, *total_occurrences);
-    //         changed_occurrence.set(true);
-    //         selected_occurrence.set(value);
-    //     }
-    // };
-    // let onclick_next = {
-    //     move |_| {
-    //         let value = *selected_occurrence + 1;
-    //         let value = value.clamp(0, *total_occurrences);
-    //         changed_occurrence.set(true);
-    //         selected_occurrence.set(value);
-    //     }
-    // };
-    // let oninput = {
-    //     let search_value = search_value.clone();
-    //     move |event: InputEvent| {
-    //         // When events are created the target is undefined, it's only
-    //         // when dispatched does the target get added.
-    //         let target = event.target();
-    //         // Events can bubble so this listener might catch events from child
-    //         // elements which are not of type HtmlInputElement
-    //         let input = target
-    //             .and_then(|t| t.dyn_into::<HtmlInputElement>().ok())
-    //             .unwrap();
-    //         search_value.set(input.value());
-    //         // TODO: search()
-    //     }
-    // };
+    let on_open = {
+        let view_state = view_state.clone();
+        let show_details = show_details.clone();
+        Callback::from(move |index| {
+            view_state.set(ViewState {
+                selected: Some(index),
+                ..(*view_state).clone()
+            });
+            show_details.set(true);
+        })
+    };
+    let on_bookmark = {
+        let bookmarks = bookmarks.clone();
+        let on_bookmarks = on_bookmarks.clone();
+        Callback::from(move |index| on_bookmarks.emit((**bookmarks).clone().toggle(index)))
+    };
+    let on_jump = {
+        let view_state = view_state.clone();
+        let scroll_to = scroll_to.clone();
+        let loaded = loaded.clone();
+        Callback::from(move |event| {
+            if let Some(state) = &loaded {
+                reveal(&view_state, state, event);
+                scroll_to.set(Some(event));
+            }
+        })
+    };
+    let on_close_bookmarks = {
+        let show_bookmarks = show_bookmarks.clone();
+        move |_| show_bookmarks.set(false)
+    };
+
+    let on_close_details = {
+        let show_details = show_details.clone();
+        move |_| show_details.set(false)
+    };
+
+    let palette_entries = use_memo(
+        (
+            loaded.as_ref().map(|state| Rc::as_ptr(state) as usize),
+            *show_palette,
+        ),
+        {
+            let loaded = loaded.clone();
+            let show_palette = *show_palette;
+            // Only listed while the palette is open, it walks all spans.
+            move |_| {
+                if show_palette {
+                    registry(loaded.as_deref())
+                } else {
+                    vec![]
+                }
+            }
+        },
+    );
+    let on_close_palette = {
+        let show_palette = show_palette.clone();
+        move |_| show_palette.set(false)
+    };
+
+    use_effect_with((), |_| apply_theme());
+
+    let permalink = use_state(|| parse_fragment(BrowserHistory::new().location().hash()));
+    {
+        let permalink = permalink.clone();
+        use_effect_with((), move |_| {
+            let listener = EventListener::new(&gloo::utils::window(), "hashchange", move |_| {
+                permalink.set(parse_fragment(BrowserHistory::new().location().hash()));
+            });
+            move || drop(listener)
+        });
+    }
+    {
+        let view_state = view_state.clone();
+        let scroll_to = scroll_to.clone();
+        let loaded = loaded.clone();
+        use_effect_with(
+            (
+                loaded.as_ref().map(|state| Rc::as_ptr(state) as usize),
+                *permalink,
+            ),
+            move |(_, event)| {
+                let (Some(state), Some(event)) = (loaded, *event) else {
+                    return;
+                };
+                if event >= state.events.len() {
+                    log::warn!("The linked event {event} is not in the log");
+                    return;
+                }
+                reveal(&view_state, &state, event);
+                scroll_to.set(Some(event));
+            },
+        );
+    }
 
     let upload_oninput = {
         let upload_value = upload_value.clone();
@@ -153,42 +544,121 @@ fn App() -> Html {
         }
     };
 
+    let on_text_filters = {
+        let view_state = view_state.clone();
+        move |text_filters| {
+            view_state.set(ViewState {
+                text_filters,
+                ..(*view_state).clone()
+            })
+        }
+    };
+
+    let on_field_filters = {
+        let view_state = view_state.clone();
+        move |field_filters| {
+            view_state.set(ViewState {
+                field_filters,
+                ..(*view_state).clone()
+            })
+        }
+    };
+
+    let on_time_range = {
+        let view_state = view_state.clone();
+        move |time_range| {
+            view_state.set(ViewState {
+                time_range,
+                ..(*view_state).clone()
+            })
+        }
+    };
+
+    let on_query = {
+        let view_state = view_state.clone();
+        move |query| {
+            view_state.set(ViewState {
+                query,
+                ..(*view_state).clone()
+            })
+        }
+    };
+
     let on_select = {
-        let level_filter = level_filter.clone();
-        move |new_value| level_filter.set(new_value)
+        let view_state = view_state.clone();
+        Callback::from(move |filter| {
+            view_state.set(ViewState {
+                filter,
+                ..(*view_state).clone()
+            })
+        })
     };
 
-    let oncreate = {
-        let show_upload = show_upload.clone();
-        move |_| show_upload.set(true)
+    let on_revision = {
+        let view_state = view_state.clone();
+        move |revision: Option<String>| {
+            let source = view_state
+                .source
+                .clone()
+                .map(|source| Source { revision, ..source });
+            // Node and event indices are specific to a revision.
+            set_source(
+                &view_state,
+                ViewState {
+                    source,
+                    expanded: Default::default(),
+                    revealed: Default::default(),
+                    selected: None,
+                    ..(*view_state).clone()
+                },
+            );
+        }
+    };
+
+    let on_context = {
+        let view_state = view_state.clone();
+        move |event: Event| {
+            // When events are created the target is undefined, it's only
+            // when dispatched does the target get added.
+            let target = event.target();
+            // Events can bubble so this listener might catch events from child
+            // elements which are not of type HtmlInputElement
+            let input = target
+                .and_then(|t| t.dyn_into::<HtmlInputElement>().ok())
+                .unwrap();
+            // Revealed events only make sense together with the context they extend.
+            view_state.set(ViewState {
+                context: input.value().parse().unwrap_or_default(),
+                revealed: Default::default(),
+                ..(*view_state).clone()
+            })
+        }
     };
 
     let onupload = {
-        let gist_clone = gist.clone();
-        let state_clone = state.clone();
+        let log = log.clone();
         let show_upload = show_upload.clone();
+        let progress = progress.clone();
+        let view_state = view_state.clone();
         move |_| {
-            let gist_clone = gist_clone.clone();
-            let state_clone = state_clone.clone();
+            let log = log.clone();
             let show_upload = show_upload.clone();
             let upload_value = upload_value.clone();
+            let progress = progress.clone();
+            let view_state = view_state.clone();
             wasm_bindgen_futures::spawn_local(async move {
-                let upload_value = upload_value.clone();
+                let upload_value = (*upload_value).clone();
+                let progress_clone = progress.clone();
+                let content = upload_value.clone();
                 let local = || async move {
-                    let gist = CreateGist {
-                        public: true,
-                        files: {
-                            let mut map = BTreeMap::new();
-                            map.insert(
-                                "trace.json".into(),
-                                CreateGistFile {
-                                    content: (*upload_value).clone(),
-                                },
-                            );
-                            map
-                        },
-                        description: Some("probe-rs debug trace".into()),
-                    };
+                    progress_clone.set(Some(Progress::Compressing));
+                    // Give the browser a chance to show the progress before compressing.
+                    TimeoutFuture::new(0).await;
+                    let gist = CreateGist::new(&content, Some("probe-rs debug trace".into()))
+                        .map_err(|e| LoadError::Encode(e.to_string()))?;
+                    progress_clone.set(Some(Progress::Uploading {
+                        files: gist.files.len(),
+                    }));
 
                     let mut request = Request::post("https://api.github.com/gists")
                         .header("X-GitHub-Api-Version", GH_API_VERSION);
@@ -198,115 +668,125 @@ fn App() -> Html {
                     }
 
                     let response = request
-                        .json(&gist)?
+                        .json(&gist)
+                        .map_err(|e| LoadError::Encode(e.to_string()))?
                         .send()
                         .await
-                        .map_err(|e| anyhow::anyhow!("Failed to load file").context(e))?;
+                        .map_err(LoadError::network)?;
                     if response.status() == 201 {
-                        let response: Gist = response
-                            .json()
-                            .await
-                            .map_err(|e| anyhow::anyhow!("Failed to load file").context(e))?;
+                        let response: Gist = response.json().await.map_err(LoadError::network)?;
                         Ok(response)
                     } else {
-                        anyhow::bail!("Failed to load file with: {}", response.status());
+                        Err(LoadError::http(&response))
                     }
                 };
-                let result = local().await;
-                if let Ok(gist) = &result {
-                    let state = gist
-                        .current_file()
-                        .map(|gist| State::new(&gist.content))
-                        .transpose()
-                        .unwrap();
-                    state_clone.set(state);
-                    let history = BrowserHistory::new();
-
-                    history
-                        .push_with_query(history.location().path(), {
-                            let mut map = HashMap::new();
-                            map.insert("gist", gist.id.clone());
-                            map
-                        })
-                        .unwrap();
+                match local().await {
+                    Ok(gist) => {
+                        let source = Source {
+                            gist: gist.id.clone().unwrap_or_default(),
+                            revision: None,
+                        };
+                        let cached = CachedLog {
+                            gist: gist.info(),
+                            etag: None,
+                            content: upload_value,
+                            annotations: Default::default(),
+                        };
+                        add_recent_log(&cached.gist, None);
+                        cache_upload(&cached).await;
+                        // The uploaded log is still at hand, no need to reassemble it from the gist.
+                        log.set(LogStatus::parse(source.clone(), cached));
+
+                        // Keep the filters and time mode, the rest belongs to the previous log.
+                        set_source(
+                            &view_state,
+                            ViewState {
+                                source: Some(source),
+                                filter: view_state.filter.clone(),
+                                text_filters: view_state.text_filters.clone(),
+                                field_filters: view_state.field_filters.clone(),
+                                query: view_state.query.clone(),
+                                time_mode: view_state.time_mode,
+                                ..Default::default()
+                            },
+                        );
+                    }
+                    Err(error) => log.set(LogStatus::Failed(error)),
                 }
-                gist_clone.set(result);
+                progress.set(None);
                 show_upload.set(false);
             });
         }
     };
 
     // https://api.github.com/gists/14a826cbe3a884fc3207cde3dfd38817
-    let gist_clone = gist.clone();
-    let state_clone = state.clone();
-    use_effect_with((), move |_| {
-        let gist = gist_clone;
-        wasm_bindgen_futures::spawn_local(async move {
-            let local = move || async {
-                let location = BrowserHistory::new()
-                    .location()
-                    .query::<HashMap<String, String>>()?;
-
-                let hash = location
-                    .get("gist")
-                    .ok_or_else(|| anyhow::anyhow!("A gist hash must be provided"))?;
-
-                log::debug!("Loading gist {}", hash);
-                let mut request = Request::get(&format!("https://api.github.com/gists/{hash}"))
-                    .header("X-GitHub-Api-Version", GH_API_VERSION);
-
-                if let Some(token) = gh_token() {
-                    request = request.header("Authorization", &format!("Bearer {token}"));
-                }
+    let log_clone = log.clone();
+    let progress_clone = progress.clone();
+    use_effect_with(view_state.source.clone(), move |source| {
+        let source = source.clone();
+        let progress = progress_clone;
+        let log = log_clone;
+
+        let Some(source) = source else {
+            log.set(LogStatus::Landing);
+            return;
+        };
+        if matches!(&*log, LogStatus::Loaded { source: loaded, .. } if *loaded == source) {
+            // Just uploaded, the log is already there.
+            return;
+        }
 
-                let response = request
-                    .send()
-                    .await
-                    .map_err(|e| anyhow::anyhow!("Failed to load file").context(e))?;
+        log.set(LogStatus::Loading);
+        wasm_bindgen_futures::spawn_local(async move {
+            let on_progress = {
+                let progress = progress.clone();
+                move |p| progress.set(Some(p))
+            };
+            let revision = source.revision.as_deref();
 
-                if response.status() == 200 {
-                    let response: Gist = response
-                        .json()
-                        .await
-                        .map_err(|e| anyhow::anyhow!("Failed to load file").context(e))?;
-                    Ok(response)
-                } else {
-                    anyhow::bail!("Failed to load file with: {}", response.status());
+            let status = match load_gist(&source.gist, revision, &on_progress).await {
+                Ok(cached) => {
+                    add_recent_log(&cached.gist, revision);
+                    LogStatus::parse(source.clone(), cached)
                 }
+                Err(error) => LogStatus::Failed(error),
             };
-
-            let result = local().await;
-
-            if let Ok(gist) = &result {
-                let current_file = gist.current_file().unwrap();
-
-                log::debug!("Using file {} from gist", current_file.filename);
-
-                let content = if current_file.truncated {
-                    log::info!(
-                        "File {} is truncated, downloading complete file...",
-                        current_file.filename
-                    );
-                    Request::get(&gist.current_file().unwrap().raw_url)
-                        .send()
-                        .await
-                        .unwrap()
-                        .text()
-                        .await
-                        .unwrap()
-                } else {
-                    current_file.content.clone()
-                };
-
-                let state = State::new(&content).ok();
-                state_clone.set(state);
-            }
-            gist.set(result);
+            progress.set(None);
+            log.set(status);
         });
     });
 
+    let on_dismiss_url_error = {
+        let url_error = url_error.clone();
+        move |_| url_error.set(None)
+    };
+
+    let skipped_notice = match &*log {
+        LogStatus::Loaded {
+            state, identity, ..
+        } if !state.skipped.is_empty() && *skipped_dismissed != *identity => {
+            let error = LoadError::SkippedLines {
+                count: state.skipped.len(),
+                first: state.skipped[0].to_string(),
+            };
+            let on_dismiss = {
+                let skipped_dismissed = skipped_dismissed.clone();
+                let identity = identity.clone();
+                move |_| skipped_dismissed.set(identity.clone())
+            };
+            html! {<div class="mb-3"><ErrorView {error} {on_dismiss} /></div>}
+        }
+        _ => html! {},
+    };
+
     html! {<ContextMenuProvider>
         <ContextMenu />
+        <Shortcuts on_action={on_action.clone()} />
+        {if *show_palette {
+            html!{<CommandPalette entries={palette_entries} on_action={on_action.clone()} on_close={on_close_palette} />}
+        } else {
+            html!{}
+        }}
         <div class={classes!["w-full", "h-full", "bg-white", if *show_upload { "fixed" } else { "hidden" }]}>
             <button onclick={onupload} class="border border-black px-2 py-1 m-3">{"Upload"}</button>
             <div class="w-full h-full p-3">
@@ -314,19 +794,167 @@ fn App() -> Html {
             </div>
         </div>
         <div>
-            // <label>{"Search:"}</label>
-            // <input {oninput} value={search_value.to_string()} />
-            // <button onclick={onclick_previous}>{ "<" }</button>
-            // <button onclick={onclick_next}>{ ">" }</button>
-            <LevelPicker level_filter={(*level_filter).clone()} {on_select} />
-            <button onclick={oncreate} class={classes!["ml-3","my-3", "px-2", "py-1", "border", "border-black"]}>{"Create"}</button>
-            <div class="m-3">
-                {match (&*gist, &*state) {
-                    (Ok(_gist), Some(state)) => html!{<InfoNode state={Rc::new(state.clone())} node_index={0} level_filter={level_filter.clone()} />},
-                    (Err(error), _) => error.to_string().into(),
-                    _ => unreachable!()
+            <SearchBar
+                query={view_state.search.clone()}
+                error={search_error}
+                current={current_match}
+                total={matches.len()}
+                {on_input}
+                on_previous={Callback::from(on_previous)}
+                on_next={Callback::from(on_next)}
+            />
+            <TextFilters search={view_state.search.clone()} filters={view_state.text_filters.clone()} on_change={on_text_filters} />
+            <QueryBar value={view_state.query.clone()} on_change={on_query} />
+            <LevelPicker level_filter={view_state.filter.clone()} on_select={on_select.clone()} />
+            <button onclick={on_action.reform(|_| Action::Create)} class={classes!["ml-3","my-3", "px-2", "py-1", "border", "border-black"]}>{"Create"}</button>
+            <button onclick={on_action.reform(|_| Action::ToggleTimeMode)} class={classes!["ml-3","my-3", "px-2", "py-1", "border", "border-black"]}>{format!("Time: {}", view_state.time_mode)}</button>
+            <label class={classes!["ml-3", "my-3"]} title="Events shown around each matching event, like grep -C">
+                {"Context: "}
+                <input type="number" min="0" onchange={on_context} value={view_state.context.to_string()} class={classes!["w-16", "px-2", "py-1", "border", "border-black"]} />
+            </label>
+            <ExpandBar on_expand={on_action.reform(Action::Expand)} />
+            <button onclick={on_action.reform(|_| Action::ToggleTargets)} class={classes!["ml-3","my-3", "px-2", "py-1", "border", "border-black", show_targets.then_some("bg-gray-300")]}>{"Targets"}</button>
+            <button onclick={on_action.reform(|_| Action::ToggleFacets)} class={classes!["ml-3","my-3", "px-2", "py-1", "border", "border-black", show_facets.then_some("bg-gray-300")]}>{"Fields"}</button>
+            <button onclick={on_action.reform(|_| Action::ToggleBookmarks)} class={classes!["ml-3","my-3", "px-2", "py-1", "border", "border-black", show_bookmarks.then_some("bg-gray-300")]}>{"Bookmarks"}</button>
+            <button onclick={on_action.reform(|_| Action::OpenPalette)} title="Command palette (Ctrl+K)" class={classes!["ml-3","my-3", "px-2", "py-1", "border", "border-black"]}>{"Commands"}</button>
+            {match &*log {
+                LogStatus::Loaded { gist, source, .. } if !gist.history.is_empty() => html!{<RevisionPicker history={gist.history.clone()} revision={source.revision.clone()} on_select={on_revision} />},
+                _ => html!{},
+            }}
+            {match &*log {
+                LogStatus::Loaded { state, .. } if url_error.is_none() => html!{
+                    <Timeline state={state.clone()} range={view_state.time_range} on_select={on_time_range} />
+                },
+                _ => html!{},
+            }}
+            <div class="flex">
+            {if let Some(root) = &*targets {
+                html!{<TargetSidebar root={root.clone()} level_filter={view_state.filter.clone()} {on_select} />}
+            } else {
+                html!{}
+            }}
+            {match &*log {
+                LogStatus::Loaded { state, .. } if *show_facets => html!{
+                    <FacetPanel state={state.clone()} filters={view_state.field_filters.clone()} on_change={on_field_filters} />
+                },
+                _ => html!{},
+            }}
+            <div class="m-3 grow">
+                {match (&*url_error, &*log) {
+                    (Some(error), _) => html!{<ErrorView error={error.clone()} on_dismiss={on_dismiss_url_error} />},
+                    (None, _) if progress.is_some() => html!{<LoadingView progress={*progress} />},
+                    (None, LogStatus::Loading) => html!{<LoadingView />},
+                    (None, LogStatus::Landing) => html!{<RecentLogs />},
+                    (None, LogStatus::Loaded { state, source, annotations, .. }) => html!{<>{skipped_notice}<LogView state={state.clone()} view_state={view_state.clone()} visibility={visibility.clone()} {on_open} bookmarks={(*bookmarks).clone()} {on_bookmark} annotations={match &*published {
+                        Some((gist, published)) if *gist == source.gist => published.clone(),
+                        _ => annotations.clone(),
+                    }} {on_annotate} scroll_to={*scroll_to} /></>},
+                    (None, LogStatus::Failed(error)) => html!{<ErrorView error={error.clone()} />},
                 }}
             </div>
+            {match &*log {
+                LogStatus::Loaded { state, identity, .. } if *show_bookmarks => html!{
+                    <BookmarkPanel state={state.clone()} bookmarks={(*bookmarks).clone()} identity={identity.clone()} on_change={on_bookmarks} {on_jump} on_close={on_close_bookmarks} />
+                },
+                _ => html!{},
+            }}
+            {match (&*log, view_state.selected) {
+                (LogStatus::Loaded { state, content, .. }, Some(index)) if *show_details => {
+                    let raw = state.raw_line(content, index).map(String::from);
+                    html!{<DetailPanel state={state.clone()} {index} {raw} on_close={on_close_details} />}
+                }
+                _ => html!{},
+            }}
+            </div>
         </div>
     </ContextMenuProvider>}
 }
+
+/// What the viewer currently shows.
+#[derive(Clone, PartialEq)]
+enum LogStatus {
+    Loading,
+    /// No log was requested.
+    Landing,
+    Loaded {
+        source: Source,
+        gist: GistInfo,
+        state: Rc<State>,
+        /// The raw log, for showing the original line of an event.
+        content: Rc<str>,
+        /// Identifies the log for storing its bookmarks.
+        identity: String,
+        annotations: Rc<Annotations>,
+    },
+    Failed(LoadError),
+}
+
+impl LogStatus {
+    fn parse(source: Source, log: CachedLog) -> Self {
+        match State::new(&log.content) {
+            Ok(state) if state.events.is_empty() => LogStatus::Failed(LoadError::Unparsable(
+                "the log does not contain any events".into(),
+            )),
+            Ok(state) => LogStatus::Loaded {
+                identity: log_identity(&source, &log.content),
+                source,
+                gist: log.gist,
+                state: Rc::new(state),
+                content: log.content.into(),
+                annotations: Rc::new(log.annotations),
+            },
+            Err(error) => LogStatus::Failed(LoadError::Unparsable(error.to_string())),
+        }
+    }
+}
+
+fn view_state_from_url() -> Result<ViewState, LoadError> {
+    let location = BrowserHistory::new().location();
+    ViewState::from_query(location.query_str()).map_err(|error| LoadError::InvalidParameter {
+        key: error.key,
+        value: error.value,
+        reason: error.reason,
+    })
+}
+
+/// Switches to another log, adding a browser history entry for it.
+fn set_source(view_state: &UseStateHandle<ViewState>, new_state: ViewState) {
+    let history = BrowserHistory::new();
+    let path = history.location().path().to_string();
+    history.push(format!("{path}?{}", new_state.to_query()));
+    view_state.set(new_state);
+}
+
+/// `localStorage` key of the name annotations are published under.
+const AUTHOR_KEY: &str = "annotation-author";
+
+/// The name to publish annotations under, asking for it the first time.
+fn annotation_author() -> Option<String> {
+    let stored = LocalStorage::get::<String>(AUTHOR_KEY).ok();
+    if let Some(author) = &stored {
+        return Some(author.clone());
+    }
+    let author = gloo::dialogs::prompt("Your name, shown with your annotations", None)?;
+    let author = author.trim().to_string();
+    if author.is_empty() {
+        return None;
+    }
+    if let Err(error) = LocalStorage::set(AUTHOR_KEY, &author) {
+        log::warn!("Failed to store the annotation author: {error}");
+    }
+    Some(author)
+}
+
+/// Selects `event`, expanding its spans and showing it even if the filters hide it.
+fn reveal(view_state: &UseStateHandle<ViewState>, state: &State, event: usize) {
+    let mut expanded = view_state.expanded.clone();
+    expanded.extend(state.ancestors(event));
+    let mut revealed = view_state.revealed.clone();
+    revealed.insert(event);
+    view_state.set(ViewState {
+        expanded,
+        revealed,
+        selected: Some(event),
+        ..(**view_state).clone()
+    });
+}
@@ -0,0 +1,179 @@
+use crate::{
+    annotations::Annotations,
+    context::{RowState, Visibility},
+    proto::log_level::LogLevel,
+    rows::{flatten, Row},
+    state::{EventType, State},
+    view_state::ViewState,
+};
+
+/// A keyboard command moving the selected row or expanding and collapsing around it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Navigation {
+    Down,
+    Up,
+    /// Collapses the selected span, or moves to the enclosing span.
+    Collapse,
+    /// Expands the selected span, or moves into an expanded one.
+    Expand,
+    /// Toggles the selected span.
+    Toggle,
+    Parent,
+    /// Moves to the next span in the same parent.
+    NextSibling,
+    /// Moves to the next visible warning or error, expanding the spans around it.
+    NextWarning,
+    PreviousWarning,
+}
+
+impl Navigation {
+    /// Returns `view_state` with the selection moved and spans expanded or collapsed.
+    pub fn apply(
+        self,
+        state: &State,
+        view_state: &ViewState,
+        visibility: &Visibility,
+    ) -> ViewState {
+        let mut view_state = view_state.clone();
+        let rows = displayed_rows(state, &view_state, visibility);
+        let selected = view_state.selected;
+        let span = selected.and_then(|selected| state.node_of(selected));
+        let parent = selected
+            .and_then(|selected| state.ancestors(selected).first().copied())
+            .and_then(|node| state.nodes[node].index);
+        let position = selected.and_then(|selected| rows.iter().position(|row| *row == selected));
+
+        match self {
+            // Rows are in event order, so a selection that isn't displayed still has neighbours.
+            Navigation::Down => {
+                let next = match (position, selected) {
+                    (Some(position), _) => rows.get(position + 1),
+                    (None, Some(selected)) => rows.iter().find(|row| **row > selected),
+                    (None, None) => rows.first(),
+                };
+                view_state.selected = next.copied().or(selected);
+            }
+            Navigation::Up => {
+                let previous = match (position, selected) {
+                    (Some(position), _) => position.checked_sub(1).map(|position| &rows[position]),
+                    (None, Some(selected)) => rows.iter().rev().find(|row| **row < selected),
+                    (None, None) => rows.last(),
+                };
+                view_state.selected = previous.copied().or(selected);
+            }
+            Navigation::Collapse => match span {
+                Some(node) if view_state.expanded.contains(&node) => {
+                    view_state.expanded.remove(&node);
+                }
+                _ => view_state.selected = parent.or(selected),
+            },
+            Navigation::Expand => match span {
+                Some(node) if !view_state.expanded.contains(&node) => {
+                    view_state.expanded.insert(node);
+                }
+                Some(node) => {
+                    let first_child = position
+                        .and_then(|position| rows.get(position + 1))
+                        .filter(|row| state.ancestors(**row).first() == Some(&node));
+                    view_state.selected = first_child.copied().or(selected);
+                }
+                None => (),
+            },
+            Navigation::Toggle => {
+                if let Some(node) = span {
+                    if !view_state.expanded.remove(&node) {
+                        view_state.expanded.insert(node);
+                    }
+                }
+            }
+            Navigation::Parent => view_state.selected = parent.or(selected),
+            Navigation::NextSibling => {
+                if let Some(selected) = selected {
+                    view_state.selected =
+                        next_sibling(state, &view_state, selected).or(Some(selected));
+                }
+            }
+            Navigation::NextWarning | Navigation::PreviousWarning => {
+                let warnings = (0..state.events.len())
+                    .filter(|index| {
+                        state.is_message(*index)
+                            && visibility.rows.get(*index) != Some(&RowState::Hidden)
+                            && matches!(
+                                state.events[*index].level,
+                                LogLevel::Warn | LogLevel::Error
+                            )
+                    })
+                    .collect::<Vec<_>>();
+                let target = if self == Navigation::NextWarning {
+                    warnings
+                        .iter()
+                        .find(|&&w| selected.is_none_or(|s| w > s))
+                        .or(warnings.first())
+                } else {
+                    warnings
+                        .iter()
+                        .rev()
+                        .find(|&&w| selected.is_none_or(|s| w < s))
+                        .or(warnings.last())
+                };
+                if let Some(&target) = target {
+                    view_state.expanded.extend(state.ancestors(target));
+                    view_state.selected = Some(target);
+                }
+            }
+        }
+        view_state
+    }
+}
+
+/// Returns the `enter` event of the next span named `name` after `selected`, wrapping around.
+pub fn find_span(state: &State, selected: Option<usize>, name: &str) -> Option<usize> {
+    let spans = state
+        .nodes
+        .iter()
+        .filter_map(|node| node.index)
+        .filter(|index| {
+            state.events[*index]
+                .span
+                .as_ref()
+                .is_some_and(|span| span.name == name)
+        })
+        .collect::<Vec<_>>();
+    spans
+        .iter()
+        .find(|&&span| selected.is_none_or(|selected| span > selected))
+        .or(spans.first())
+        .copied()
+}
+
+/// The span header of the next span after `selected` in the same parent.
+fn next_sibling(state: &State, view_state: &ViewState, selected: usize) -> Option<usize> {
+    let parent = state.parents.get(selected).copied().flatten()?;
+    let siblings = &state.nodes[parent].children;
+    let position = siblings.iter().position(|child| match child {
+        EventType::Message(index) => *index == selected,
+        EventType::Node(node) => state.nodes[*node].index == Some(selected),
+    })?;
+    siblings[position + 1..]
+        .iter()
+        .find_map(|child| match child {
+            EventType::Node(node) if view_state.shows_span(&state.nodes[*node]) => {
+                state.nodes[*node].index
+            }
+            _ => None,
+        })
+}
+
+/// Returns the event indices of the rows currently on screen in display order.
+///
+/// Like [`State::rows`], but skipping hidden events and the contents of collapsed spans.
+pub fn displayed_rows(
+    state: &State,
+    view_state: &ViewState,
+    visibility: &Visibility,
+) -> Vec<usize> {
+    flatten(state, view_state, visibility, &Annotations::default())
+        .iter()
+        .filter_map(Row::selectable)
+        .collect()
+}
@@ -0,0 +1,16 @@
+/// Returns a link to the event with index `event`, based on the URL of the current view.
+///
+/// The event is given in the fragment as `#e=1234`, so the filters of the view are kept.
+pub fn permalink(href: &str, event: usize) -> String {
+    let base = href.split_once('#').map_or(href, |(base, _)| base);
+    format!("{base}#e={event}")
+}
+
+/// Returns the event a URL fragment like `#e=1234` points to.
+pub fn parse_fragment(fragment: &str) -> Option<usize> {
+    let fragment = fragment.strip_prefix('#').unwrap_or(fragment);
+    fragment
+        .split('&')
+        .find_map(|part| part.strip_prefix("e="))
+        .and_then(|event| event.parse().ok())
+}
@@ -1,24 +1,61 @@
 pub mod log_level;
 
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::timestamp::parse_timestamp;
 
 use self::log_level::LogLevel;
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Fields {
     pub message: String,
+    /// All other fields recorded with the event.
+    #[serde(flatten)]
+    pub values: BTreeMap<String, Value>,
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Span {
     pub name: String,
+    #[serde(flatten)]
+    pub fields: BTreeMap<String, Value>,
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Event {
+    #[serde(default)]
+    pub timestamp: Option<String>,
     pub fields: Fields,
     pub level: LogLevel,
     pub span: Option<Span>,
     pub spans: Option<Vec<Span>>,
     pub target: String,
 }
+
+/// Formats a field value for display, strings are shown without quotes.
+pub fn format_value(value: &Value) -> String {
+    match value {
+        Value::String(value) => value.clone(),
+        value => value.to_string(),
+    }
+}
+
+impl Event {
+    /// The time of the event in microseconds since the Unix epoch.
+    pub fn time(&self) -> Option<i64> {
+        self.timestamp.as_deref().and_then(parse_timestamp)
+    }
+
+    /// Looks up a field of the event, or of the innermost span that has it.
+    pub fn field(&self, name: &str) -> Option<&Value> {
+        self.fields.values.get(name).or_else(|| {
+            self.span
+                .iter()
+                .chain(self.spans.iter().flatten().rev())
+                .find_map(|span| span.fields.get(name))
+        })
+    }
+}
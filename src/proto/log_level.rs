@@ -56,7 +56,7 @@ impl LogLevel {
         }
     }
 
-    fn num(&self) -> usize {
+    pub(crate) fn num(&self) -> usize {
         match self {
             LogLevel::Trace => 0,
             LogLevel::Debug => 1,
@@ -0,0 +1,491 @@
+//! A small query language for filtering events.
+//!
+//! A query is a list of conditions combined with `and`, `or`, `not` and parentheses,
+//! conditions next to each other are combined with `and`:
+//!
+//! ```text
+//! level>=warn and target:probe_rs::flashing and msg~"timeout" and span:attach and field.addr=0xE000EDF0
+//! ```
+//!
+//! | Key             | Operators                       | Matches                                       |
+//! |-----------------|---------------------------------|-----------------------------------------------|
+//! | `level`         | `=` `!=` `<` `<=` `>` `>=`      | the event level                               |
+//! | `target`        | `:` `=` `!=` `~`                | the target, `:` includes submodules           |
+//! | `msg`           | `:` `=` `!=` `~`                | the message                                   |
+//! | `span`          | `:` `=` `!=` `~`                | the name of any span the event is in          |
+//! | `field.<name>`  | `:` `=` `!=` `~` `<` `<=` `>` `>=` | a field of the event or one of its spans  |
+//!
+//! `:` matches a substring, `=` the whole value and `~` a regular expression, all but `=`
+//! ignore case. Numbers, including hexadecimal ones, are compared by value. Words without a
+//! key are searched for everywhere in the event, like the search bar does.
+
+use std::{cell::RefCell, cmp::Ordering, collections::HashMap, fmt::Display, rc::Rc, str::FromStr};
+
+use crate::{
+    level_filter::is_within,
+    proto::{format_value, log_level::LogLevel, Event},
+    search::{event_matches, SearchQuery},
+};
+use regex::{Regex, RegexBuilder};
+
+/// Parsed queries are cached, as they are evaluated for every row on every render.
+const CACHE_SIZE: usize = 64;
+
+thread_local! {
+    static CACHE: RefCell<HashMap<String, Result<Rc<Query>, QueryError>>> = RefCell::new(HashMap::new());
+}
+
+/// A parsed query, see the [module documentation](self) for the syntax.
+#[derive(Debug, Clone)]
+pub enum Query {
+    And(Box<Query>, Box<Query>),
+    Or(Box<Query>, Box<Query>),
+    Not(Box<Query>),
+    Level(Comparison, LogLevel),
+    Target(Match),
+    Message(Match),
+    Span(Match),
+    Field(String, Match),
+    /// A word searched for in the whole event.
+    Text(Regex),
+}
+
+/// How a value is compared to the one in the query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Equal,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+impl Comparison {
+    fn holds(self, ordering: Option<Ordering>) -> bool {
+        let Some(ordering) = ordering else {
+            return false;
+        };
+        match self {
+            Comparison::Equal => ordering.is_eq(),
+            Comparison::Less => ordering.is_lt(),
+            Comparison::LessOrEqual => ordering.is_le(),
+            Comparison::Greater => ordering.is_gt(),
+            Comparison::GreaterOrEqual => ordering.is_ge(),
+        }
+    }
+}
+
+/// How a text value of an event is matched.
+#[derive(Debug, Clone)]
+pub enum Match {
+    /// The whole text, or the same number.
+    Equal(String),
+    /// The module or one of its submodules.
+    Within(String),
+    Regex(Regex),
+    /// A number compared to the given one.
+    Number(Comparison, Number),
+}
+
+/// A number of a query or a field value.
+///
+/// Integers are kept exact, 64 bit addresses do not fit into the mantissa of an `f64`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Number {
+    Integer(i128),
+    Float(f64),
+}
+
+impl Number {
+    fn compare(&self, other: &Number) -> Option<Ordering> {
+        match (*self, *other) {
+            (Number::Integer(a), Number::Integer(b)) => Some(a.cmp(&b)),
+            (a, b) => a.as_f64().partial_cmp(&b.as_f64()),
+        }
+    }
+
+    fn as_f64(self) -> f64 {
+        match self {
+            Number::Integer(number) => number as f64,
+            Number::Float(number) => number,
+        }
+    }
+}
+
+impl Match {
+    fn matches(&self, text: &str) -> bool {
+        match self {
+            Match::Equal(value) => {
+                text == value
+                    || matches!((parse_number(text), parse_number(value)), (Some(a), Some(b)) if a.compare(&b) == Some(Ordering::Equal))
+            }
+            Match::Within(module) => is_within(text, module),
+            Match::Regex(regex) => regex.is_match(text),
+            Match::Number(comparison, value) => {
+                parse_number(text).is_some_and(|number| comparison.holds(number.compare(value)))
+            }
+        }
+    }
+}
+
+/// Parses decimal and `0x` prefixed hexadecimal numbers.
+fn parse_number(text: &str) -> Option<Number> {
+    let text = text.trim();
+    match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16)
+            .ok()
+            .map(|number| Number::Integer(number.into())),
+        None => text
+            .parse()
+            .map(Number::Integer)
+            .ok()
+            .or_else(|| text.parse().ok().map(Number::Float)),
+    }
+}
+
+/// A query that could not be parsed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryError {
+    /// Byte offset into the query where the error was found.
+    pub position: usize,
+    pub message: String,
+}
+
+impl QueryError {
+    fn new(position: usize, message: impl Display) -> Self {
+        Self {
+            position,
+            message: message.to_string(),
+        }
+    }
+}
+
+impl Display for QueryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position + 1)
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+impl Query {
+    /// Parses `input`, an empty query is `None`.
+    pub fn parse(input: &str) -> Result<Option<Self>, QueryError> {
+        let mut parser = Parser { input, position: 0 };
+        parser.skip_whitespace();
+        if parser.at_end() {
+            return Ok(None);
+        }
+
+        let query = parser.parse_or()?;
+        parser.skip_whitespace();
+        if !parser.at_end() {
+            return Err(QueryError::new(parser.position, "unexpected `)`"));
+        }
+        Ok(Some(query))
+    }
+
+    /// Like [`Query::parse`], but reuses earlier results for the same input.
+    pub fn parse_cached(input: &str) -> Result<Option<Rc<Self>>, QueryError> {
+        CACHE.with(|cache| {
+            let mut cache = cache.borrow_mut();
+            if let Some(query) = cache.get(input) {
+                return query.clone().map(Some);
+            }
+
+            let query = match Query::parse(input) {
+                Ok(None) => return Ok(None),
+                Ok(Some(query)) => Ok(Rc::new(query)),
+                Err(error) => Err(error),
+            };
+            if cache.len() >= CACHE_SIZE {
+                cache.clear();
+            }
+            cache.insert(input.to_string(), query.clone());
+            query.map(Some)
+        })
+    }
+
+    /// Returns `true` if `event` satisfies the query.
+    pub fn matches(&self, event: &Event) -> bool {
+        match self {
+            Query::And(a, b) => a.matches(event) && b.matches(event),
+            Query::Or(a, b) => a.matches(event) || b.matches(event),
+            Query::Not(query) => !query.matches(event),
+            Query::Level(comparison, level) => comparison.holds(event.level.partial_cmp(level)),
+            Query::Target(matcher) => matcher.matches(&event.target),
+            Query::Message(matcher) => matcher.matches(&event.fields.message),
+            Query::Span(matcher) => event
+                .span
+                .iter()
+                .chain(event.spans.iter().flatten())
+                .any(|span| matcher.matches(&span.name)),
+            Query::Field(name, matcher) => event
+                .field(name)
+                .is_some_and(|value| matcher.matches(&format_value(value))),
+            Query::Text(regex) => event_matches(event, regex),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operator {
+    Contains,
+    Equal,
+    NotEqual,
+    Regex,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+impl Operator {
+    /// Longer operators first, so `>=` isn't read as `>`.
+    const ALL: [(&'static str, Operator); 8] = [
+        (">=", Operator::GreaterOrEqual),
+        ("<=", Operator::LessOrEqual),
+        ("!=", Operator::NotEqual),
+        ("=", Operator::Equal),
+        ("~", Operator::Regex),
+        (">", Operator::Greater),
+        ("<", Operator::Less),
+        (":", Operator::Contains),
+    ];
+
+    fn comparison(self) -> Option<Comparison> {
+        match self {
+            Operator::Equal => Some(Comparison::Equal),
+            Operator::Less => Some(Comparison::Less),
+            Operator::LessOrEqual => Some(Comparison::LessOrEqual),
+            Operator::Greater => Some(Comparison::Greater),
+            Operator::GreaterOrEqual => Some(Comparison::GreaterOrEqual),
+            _ => None,
+        }
+    }
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn rest(&self) -> &str {
+        &self.input[self.position..]
+    }
+
+    fn at_end(&self) -> bool {
+        self.rest().is_empty()
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    /// Returns `true` if `keyword` comes next as a whole word.
+    fn at_keyword(&self, keyword: &str) -> bool {
+        let rest = self.rest().trim_start();
+        rest.get(..keyword.len())
+            .is_some_and(|word| word.eq_ignore_ascii_case(keyword))
+            && rest[keyword.len()..]
+                .chars()
+                .next()
+                .is_none_or(|c| c.is_whitespace() || c == '(' || c == ')')
+    }
+
+    /// Consumes `keyword` if it comes next as a whole word.
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let matches = self.at_keyword(keyword);
+        if matches {
+            self.skip_whitespace();
+            self.position += keyword.len();
+        }
+        matches
+    }
+
+    fn parse_or(&mut self) -> Result<Query, QueryError> {
+        let mut query = self.parse_and()?;
+        while self.eat_keyword("or") {
+            query = Query::Or(Box::new(query), Box::new(self.parse_and()?));
+        }
+        Ok(query)
+    }
+
+    fn parse_and(&mut self) -> Result<Query, QueryError> {
+        let mut query = self.parse_not()?;
+        loop {
+            self.skip_whitespace();
+            if self.at_end() || self.peek() == Some(')') || self.at_keyword("or") {
+                return Ok(query);
+            }
+            self.eat_keyword("and");
+            query = Query::And(Box::new(query), Box::new(self.parse_not()?));
+        }
+    }
+
+    fn parse_not(&mut self) -> Result<Query, QueryError> {
+        if self.eat_keyword("not") {
+            Ok(Query::Not(Box::new(self.parse_not()?)))
+        } else {
+            self.parse_primary()
+        }
+    }
+
+    fn parse_primary(&mut self) -> Result<Query, QueryError> {
+        self.skip_whitespace();
+        match self.peek() {
+            None => Err(QueryError::new(self.position, "expected a condition")),
+            Some('(') => {
+                self.position += 1;
+                let query = self.parse_or()?;
+                self.skip_whitespace();
+                if self.peek() != Some(')') {
+                    return Err(QueryError::new(self.position, "expected `)`"));
+                }
+                self.position += 1;
+                Ok(query)
+            }
+            Some(')') => Err(QueryError::new(self.position, "expected a condition")),
+            Some(_) => self.parse_condition(),
+        }
+    }
+
+    fn parse_condition(&mut self) -> Result<Query, QueryError> {
+        let start = self.position;
+        let key_length = self
+            .rest()
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '.'))
+            .unwrap_or(self.rest().len());
+        let key = &self.input[start..start + key_length];
+
+        if is_key(key) {
+            self.position += key_length;
+            self.skip_whitespace();
+            let operator = Operator::ALL
+                .iter()
+                .find(|(symbol, _)| self.rest().starts_with(symbol));
+            if let Some((symbol, operator)) = operator {
+                self.position += symbol.len();
+                self.skip_whitespace();
+                let value_start = self.position;
+                let value = self.parse_value()?;
+                return condition(key, *operator, &value)
+                    .map_err(|message| QueryError::new(value_start, message));
+            }
+            // Not followed by an operator, the key is just a word to search for.
+            self.position = start;
+        }
+
+        let word = self.parse_value()?;
+        SearchQuery::new(word)
+            .matcher()
+            .map(Query::Text)
+            .map_err(|error| QueryError::new(start, error))
+    }
+
+    /// Parses a quoted string or a word up to the next whitespace or parenthesis.
+    fn parse_value(&mut self) -> Result<String, QueryError> {
+        if self.peek() == Some('"') {
+            let start = self.position;
+            self.position += 1;
+            let mut value = String::new();
+            let mut chars = self.rest().char_indices();
+            while let Some((offset, c)) = chars.next() {
+                match c {
+                    '"' => {
+                        self.position += offset + 1;
+                        return Ok(value);
+                    }
+                    '\\' => match chars.next() {
+                        Some((_, c)) => value.push(c),
+                        None => break,
+                    },
+                    c => value.push(c),
+                }
+            }
+            return Err(QueryError::new(start, "unterminated string"));
+        }
+
+        let length = self
+            .rest()
+            .find(|c: char| c.is_whitespace() || c == '(' || c == ')')
+            .unwrap_or(self.rest().len());
+        if length == 0 {
+            return Err(QueryError::new(self.position, "expected a value"));
+        }
+        let value = self.rest()[..length].to_string();
+        self.position += length;
+        Ok(value)
+    }
+}
+
+fn is_key(key: &str) -> bool {
+    matches!(key, "level" | "target" | "msg" | "message" | "span")
+        || key
+            .strip_prefix("field.")
+            .is_some_and(|name| !name.is_empty())
+}
+
+/// Builds the condition `key operator value`.
+fn condition(key: &str, operator: Operator, value: &str) -> Result<Query, String> {
+    let negate = operator == Operator::NotEqual;
+    let operator = if negate { Operator::Equal } else { operator };
+
+    let query = if key == "level" {
+        let level = LogLevel::from_str(value).map_err(|e| e.to_string())?;
+        let comparison = match operator {
+            Operator::Contains => Comparison::Equal,
+            operator => operator
+                .comparison()
+                .ok_or("levels can only be compared with `=`, `<` or `>`")?,
+        };
+        Query::Level(comparison, level)
+    } else {
+        let matcher = match operator {
+            Operator::Contains if key == "target" => Match::Within(value.into()),
+            Operator::Contains | Operator::Regex => {
+                let pattern = if operator == Operator::Regex {
+                    value.to_string()
+                } else {
+                    regex::escape(value)
+                };
+                Match::Regex(
+                    RegexBuilder::new(&pattern)
+                        .case_insensitive(true)
+                        .build()
+                        .map_err(|e| e.to_string())?,
+                )
+            }
+            Operator::Equal => Match::Equal(value.into()),
+            operator => {
+                let Some(name) = key.strip_prefix("field.") else {
+                    return Err(format!("{key} can't be compared by size"));
+                };
+                let number =
+                    parse_number(value).ok_or_else(|| format!("{value} is not a number"))?;
+                return Ok(Query::Field(
+                    name.into(),
+                    Match::Number(operator.comparison().expect("a comparison"), number),
+                ));
+            }
+        };
+        match key {
+            "target" => Query::Target(matcher),
+            "msg" | "message" => Query::Message(matcher),
+            "span" => Query::Span(matcher),
+            key => Query::Field(key["field.".len()..].into(), matcher),
+        }
+    };
+
+    Ok(if negate {
+        Query::Not(Box::new(query))
+    } else {
+        query
+    })
+}
@@ -0,0 +1,45 @@
+use wasm_bindgen::JsCast;
+use web_sys::HtmlInputElement;
+use yew::{classes, function_component, html, Callback, Event, Html, Properties};
+
+use log_viewer::query::Query;
+
+const HELP: &str = "e.g. level>=warn and target:probe_rs::flashing and msg~\"timeout\" \
+    and span:attach and field.addr=0xE000EDF0";
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct QueryBarProps {
+    pub(crate) value: String,
+    pub(crate) on_change: Callback<String>,
+}
+
+#[function_component(QueryBar)]
+pub fn query_bar(props: &QueryBarProps) -> Html {
+    // Filtering a large log takes a moment, so the query is only applied on enter or blur.
+    let onchange = {
+        let on_change = props.on_change.clone();
+        move |event: Event| {
+            // When events are created the target is undefined, it's only
+            // when dispatched does the target get added.
+            let target = event.target();
+            // Events can bubble so this listener might catch events from child
+            // elements which are not of type HtmlInputElement
+            let input = target
+                .and_then(|t| t.dyn_into::<HtmlInputElement>().ok())
+                .unwrap();
+            on_change.emit(input.value());
+        }
+    };
+
+    let error = Query::parse_cached(&props.value).err();
+
+    html! {<div class="flex items-center ml-3 my-3">
+        <label class="mr-2">{"Filter:"}</label>
+        <input {onchange} value={props.value.clone()} placeholder={HELP} title={HELP} class={classes!["w-1/2", "px-2", "py-1", "border", "font-mono", if error.is_some() { "border-red-500" } else { "border-black" }]} />
+        {if let Some(error) = error {
+            html!{<span class={classes!["mx-2", "text-red-500"]}>{error.to_string()}</span>}
+        } else {
+            html!{}
+        }}
+    </div>}
+}
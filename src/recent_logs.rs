@@ -0,0 +1,27 @@
+use yew::{classes, function_component, html, Html};
+
+use crate::{cache::recent_logs, gist::short_sha};
+
+#[function_component(RecentLogs)]
+pub fn recent_logs_list() -> Html {
+    let recent = recent_logs();
+
+    if recent.is_empty() {
+        return html! {<p>{"Open a log with ?gist=<id> or upload one with the Create button."}</p>};
+    }
+
+    html! {<div>
+        <h2 class="mb-2 font-bold">{"Recently viewed"}</h2>
+        <ul>
+            { for recent.iter().map(|log| html!{<li class="py-1">
+                <a href={log.query()} class={classes!["text-blue-500", "hover:underline"]}>
+                    {log.description.clone().unwrap_or_else(|| log.id.clone())}
+                </a>
+                <span class="ml-2 text-gray-500">
+                    {log.revision.as_ref().map(|rev| format!("@{} ", short_sha(rev))).unwrap_or_default()}
+                    {format!("opened {}", log.opened_at)}
+                </span>
+            </li>}) }
+        </ul>
+    </div>}
+}
@@ -0,0 +1,53 @@
+use wasm_bindgen::JsCast;
+use web_sys::HtmlSelectElement;
+use yew::{classes, function_component, html, Callback, Event, Html, Properties};
+
+use crate::gist::{short_sha, GistRevision};
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct RevisionPickerProps {
+    pub(crate) history: Vec<GistRevision>,
+    /// The pinned revision, `None` if the latest revision is shown.
+    pub(crate) revision: Option<String>,
+    pub(crate) on_select: Callback<Option<String>>,
+}
+
+#[function_component(RevisionPicker)]
+pub fn revision_picker(props: &RevisionPickerProps) -> Html {
+    let onchange = {
+        let on_select = props.on_select.clone();
+        move |event: Event| {
+            let select = event
+                .target()
+                .and_then(|t| t.dyn_into::<HtmlSelectElement>().ok())
+                .unwrap();
+            on_select.emit(Some(select.value()))
+        }
+    };
+
+    let selected = props
+        .revision
+        .clone()
+        .or_else(|| props.history.first().map(|r| r.version.clone()));
+
+    html! {
+        <select {onchange} class={classes!["ml-3", "my-3", "px-2", "py-1", "border", "border-black", "bg-white"]}>
+            { for props.history.iter().enumerate().map(|(i, revision)| {
+                let change = &revision.change_status;
+                html!{<option
+                    value={revision.version.clone()}
+                    selected={selected.as_ref() == Some(&revision.version)}
+                >
+                    {format!(
+                        "{} {} (+{} -{}){}",
+                        short_sha(&revision.version),
+                        revision.committed_at,
+                        change.additions,
+                        change.deletions,
+                        if i == 0 { " latest" } else { "" },
+                    )}
+                </option>}
+            }) }
+        </select>
+    }
+}
@@ -0,0 +1,140 @@
+use crate::{
+    annotations::Annotations,
+    context::{RowState, Visibility},
+    state::{EventType, State},
+    view_state::ViewState,
+};
+
+/// A line of the event tree as it is displayed, with the tree flattened into a list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Row {
+    /// Hidden events starting at `event`, see [`Visibility::gaps`].
+    Gap {
+        event: usize,
+        depth: usize,
+    },
+    Message {
+        event: usize,
+        depth: usize,
+    },
+    /// The header of span `node`, entered by `event`.
+    Span {
+        node: usize,
+        event: usize,
+        depth: usize,
+        expanded: bool,
+    },
+    /// The `number`th annotation of `event`.
+    Annotation {
+        event: usize,
+        number: usize,
+        depth: usize,
+    },
+}
+
+impl Row {
+    /// The event the row belongs to.
+    pub fn event(&self) -> usize {
+        match *self {
+            Row::Gap { event, .. }
+            | Row::Message { event, .. }
+            | Row::Span { event, .. }
+            | Row::Annotation { event, .. } => event,
+        }
+    }
+
+    /// Number of spans enclosing the row.
+    pub fn depth(&self) -> usize {
+        match *self {
+            Row::Gap { depth, .. }
+            | Row::Message { depth, .. }
+            | Row::Span { depth, .. }
+            | Row::Annotation { depth, .. } => depth,
+        }
+    }
+
+    /// The event shown by the row, `None` for rows that cannot be selected.
+    pub fn selectable(&self) -> Option<usize> {
+        match *self {
+            Row::Message { event, .. } | Row::Span { event, .. } => Some(event),
+            Row::Gap { .. } | Row::Annotation { .. } => None,
+        }
+    }
+}
+
+/// Returns the rows currently on screen in display order.
+///
+/// Hidden events and the contents of collapsed spans are left out, annotations follow the
+/// row of their event.
+pub fn flatten(
+    state: &State,
+    view_state: &ViewState,
+    visibility: &Visibility,
+    annotations: &Annotations,
+) -> Vec<Row> {
+    fn annotate(annotations: &Annotations, event: usize, depth: usize, rows: &mut Vec<Row>) {
+        rows.extend(
+            (0..annotations.on(event).len()).map(|number| Row::Annotation {
+                event,
+                number,
+                depth,
+            }),
+        );
+    }
+
+    fn visit(
+        state: &State,
+        view_state: &ViewState,
+        visibility: &Visibility,
+        annotations: &Annotations,
+        node: usize,
+        depth: usize,
+        rows: &mut Vec<Row>,
+    ) {
+        for child in &state.nodes[node].children {
+            match child {
+                EventType::Message(event) => {
+                    let event = *event;
+                    if visibility.gaps.contains_key(&event) {
+                        rows.push(Row::Gap { event, depth });
+                    }
+                    if visibility.rows.get(event) != Some(&RowState::Hidden) {
+                        rows.push(Row::Message { event, depth });
+                        annotate(annotations, event, depth, rows);
+                    }
+                }
+                EventType::Node(child) => {
+                    let span = &state.nodes[*child];
+                    if !view_state.shows_span(span) {
+                        continue;
+                    }
+                    let expanded = span.expanded || view_state.expanded.contains(child);
+                    if let Some(event) = span.index {
+                        rows.push(Row::Span {
+                            node: *child,
+                            event,
+                            depth,
+                            expanded,
+                        });
+                        annotate(annotations, event, depth, rows);
+                    }
+                    if expanded {
+                        visit(
+                            state,
+                            view_state,
+                            visibility,
+                            annotations,
+                            *child,
+                            depth + 1,
+                            rows,
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    let mut rows = vec![];
+    visit(state, view_state, visibility, annotations, 0, 0, &mut rows);
+    rows
+}
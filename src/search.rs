@@ -0,0 +1,233 @@
+use std::{cell::RefCell, collections::HashMap, fmt::Display, ops::Range};
+
+use regex::{Regex, RegexBuilder};
+
+use crate::{
+    level_filter::is_within,
+    proto::{format_value, Event, Span},
+    state::State,
+    view_state::ViewState,
+};
+
+/// Compiled patterns are cached, as they are matched against every row on every render.
+const CACHE_SIZE: usize = 64;
+
+thread_local! {
+    static CACHE: RefCell<HashMap<SearchQuery, Result<Regex, regex::Error>>> = RefCell::new(HashMap::new());
+}
+
+/// A search pattern together with the options it is matched with.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct SearchQuery {
+    pub pattern: String,
+    /// Interpret the pattern as a regular expression instead of plain text.
+    pub regex: bool,
+    pub case_sensitive: bool,
+    /// Only match whole words.
+    pub whole_word: bool,
+}
+
+impl SearchQuery {
+    pub fn new(pattern: impl Into<String>) -> Self {
+        Self {
+            pattern: pattern.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Creates a query matching exactly `text`, e.g. a whole message.
+    pub fn exact(text: &str) -> Self {
+        Self {
+            pattern: format!("^{}$", regex::escape(text)),
+            regex: true,
+            case_sensitive: true,
+            whole_word: false,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pattern.is_empty()
+    }
+
+    /// Returns the compiled pattern, or the reason it is invalid.
+    pub fn matcher(&self) -> Result<Regex, regex::Error> {
+        CACHE.with(|cache| {
+            let mut cache = cache.borrow_mut();
+            if let Some(matcher) = cache.get(self) {
+                return matcher.clone();
+            }
+
+            let pattern = if self.regex {
+                self.pattern.clone()
+            } else {
+                regex::escape(&self.pattern)
+            };
+            let pattern = if self.whole_word {
+                format!(r"\b(?:{pattern})\b")
+            } else {
+                pattern
+            };
+            let matcher = RegexBuilder::new(&pattern)
+                .case_insensitive(!self.case_sensitive)
+                .build();
+
+            if cache.len() >= CACHE_SIZE {
+                cache.clear();
+            }
+            cache.insert(self.clone(), matcher.clone());
+            matcher
+        })
+    }
+
+    /// The options as a compact string of flags, e.g. `rc` for a case sensitive regex.
+    pub fn flags(&self) -> String {
+        [
+            (self.regex, 'r'),
+            (self.case_sensitive, 'c'),
+            (self.whole_word, 'w'),
+        ]
+        .iter()
+        .filter(|(set, _)| *set)
+        .map(|(_, flag)| flag)
+        .collect()
+    }
+
+    /// Sets the options from a string created by [`SearchQuery::flags`].
+    pub fn with_flags(mut self, flags: &str) -> Result<Self, String> {
+        for flag in flags.chars() {
+            match flag {
+                'r' => self.regex = true,
+                'c' => self.case_sensitive = true,
+                'w' => self.whole_word = true,
+                flag => return Err(format!("unknown search flag {flag}")),
+            }
+        }
+        Ok(self)
+    }
+}
+
+/// What a [`TextFilter`] does with the events matching its query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TextFilterMode {
+    /// Only show matching events.
+    Only,
+    /// Hide matching events.
+    Hide,
+}
+
+/// A search turned into a filter, optionally restricted to the events of one target.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TextFilter {
+    pub query: SearchQuery,
+    pub mode: TextFilterMode,
+    /// The filter only applies to events of this target and its children, if set.
+    pub target: Option<String>,
+    /// Only the message is matched, instead of all text [`event_matches`] looks at.
+    pub message_only: bool,
+}
+
+impl TextFilter {
+    /// Returns `true` if the filter lets `event` through.
+    pub fn show(&self, event: &Event) -> bool {
+        if let Some(target) = &self.target {
+            if !is_within(&event.target, target) {
+                return true;
+            }
+        }
+        let Ok(matcher) = self.query.matcher() else {
+            return true;
+        };
+
+        let matches = if self.message_only {
+            matcher.is_match(&event.fields.message)
+        } else {
+            event_matches(event, &matcher)
+        };
+        match self.mode {
+            TextFilterMode::Only => matches,
+            TextFilterMode::Hide => !matches,
+        }
+    }
+}
+
+impl Display for TextFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.mode {
+            TextFilterMode::Only => write!(f, "only ")?,
+            TextFilterMode::Hide => write!(f, "hide ")?,
+        }
+        if self.message_only {
+            write!(f, "message ")?;
+        }
+        write!(f, "\"{}\"", self.query.pattern)?;
+        if let Some(target) = &self.target {
+            write!(f, " in {target}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns `true` if `matcher` matches the message, target, span names or fields of `event`.
+pub fn event_matches(event: &Event, matcher: &Regex) -> bool {
+    let contains = |text: &str| matcher.is_match(text);
+
+    contains(&event.fields.message)
+        || contains(&event.target)
+        || event
+            .fields
+            .values
+            .iter()
+            .any(|(key, value)| contains(key) || contains(&format_value(value)))
+        || event
+            .span
+            .iter()
+            .chain(event.spans.iter().flatten())
+            .any(|span| span_matches(span, matcher))
+}
+
+/// Returns `true` if `matcher` matches the name or fields of `span`.
+fn span_matches(span: &Span, matcher: &Regex) -> bool {
+    matcher.is_match(&span.name)
+        || span
+            .fields
+            .iter()
+            .any(|(key, value)| matcher.is_match(key) || matcher.is_match(&format_value(value)))
+}
+
+/// Returns the indices of all visible events matching the search of `view_state`, in display order.
+///
+/// Span headers only match on the name and fields of their span.
+pub fn find_matches(state: &State, view_state: &ViewState) -> Result<Vec<usize>, regex::Error> {
+    if view_state.search.is_empty() {
+        return Ok(vec![]);
+    }
+    let matcher = view_state.search.matcher()?;
+
+    Ok(state
+        .rows()
+        .into_iter()
+        .filter(|&index| {
+            let event = &state.events[index];
+            // The `enter` event of a span header only stands for the span, its message, target
+            // and fields would otherwise count once more next to the events inside the span.
+            let matches = if state.is_message(index) {
+                event_matches(event, &matcher)
+            } else {
+                event
+                    .span
+                    .as_ref()
+                    .is_some_and(|span| span_matches(span, &matcher))
+            };
+            view_state.shows(event) && matches
+        })
+        .collect())
+}
+
+/// Returns the byte ranges of all matches of `matcher` in `text`.
+pub fn highlight_ranges(text: &str, matcher: &Regex) -> Vec<Range<usize>> {
+    matcher
+        .find_iter(text)
+        .filter(|found| !found.is_empty())
+        .map(|found| found.range())
+        .collect()
+}
@@ -0,0 +1,100 @@
+use wasm_bindgen::JsCast;
+use web_sys::{HtmlInputElement, KeyboardEvent};
+use yew::{classes, function_component, html, Callback, Html, InputEvent, Properties};
+
+use log_viewer::search::SearchQuery;
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct SearchBarProps {
+    pub(crate) query: SearchQuery,
+    /// Why the pattern can't be used, if it is an invalid regex.
+    #[prop_or_default]
+    pub(crate) error: Option<String>,
+    /// Position of the selected match, `None` if no match is selected.
+    pub(crate) current: Option<usize>,
+    pub(crate) total: usize,
+    pub(crate) on_input: Callback<SearchQuery>,
+    pub(crate) on_previous: Callback<()>,
+    pub(crate) on_next: Callback<()>,
+}
+
+#[function_component(SearchBar)]
+pub fn search_bar(props: &SearchBarProps) -> Html {
+    let oninput = {
+        let on_input = props.on_input.clone();
+        let query = props.query.clone();
+        move |event: InputEvent| {
+            // When events are created the target is undefined, it's only
+            // when dispatched does the target get added.
+            let target = event.target();
+            // Events can bubble so this listener might catch events from child
+            // elements which are not of type HtmlInputElement
+            let input = target
+                .and_then(|t| t.dyn_into::<HtmlInputElement>().ok())
+                .unwrap();
+            on_input.emit(SearchQuery {
+                pattern: input.value(),
+                ..query.clone()
+            });
+        }
+    };
+
+    let toggle = |title: &'static str, label: &'static str, active: bool, toggled: SearchQuery| {
+        let on_input = props.on_input.clone();
+        let onclick = move |_| on_input.emit(toggled.clone());
+        html! {<button {onclick} {title} class={classes!["ml-1", "px-2", "py-1", "border", "border-black", "font-mono", active.then_some("bg-gray-300")]}>{label}</button>}
+    };
+
+    let onkeydown = {
+        let on_previous = props.on_previous.clone();
+        let on_next = props.on_next.clone();
+        move |event: KeyboardEvent| {
+            if event.key() == "Enter" {
+                event.prevent_default();
+                if event.shift_key() {
+                    on_previous.emit(())
+                } else {
+                    on_next.emit(())
+                }
+            }
+        }
+    };
+
+    let onclick_previous = {
+        let on_previous = props.on_previous.clone();
+        move |_| on_previous.emit(())
+    };
+    let onclick_next = {
+        let on_next = props.on_next.clone();
+        move |_| on_next.emit(())
+    };
+
+    let button = classes![
+        "px-2",
+        "py-1",
+        "border",
+        "border-black",
+        "disabled:text-gray-400"
+    ];
+
+    let query = &props.query;
+
+    html! {<div class="flex items-center ml-3 my-3">
+        <label class="mr-2">{"Search:"}</label>
+        <input id="search" {oninput} {onkeydown} value={props.query.pattern.clone()} class={classes!["px-2", "py-1", "border", if props.error.is_some() { "border-red-500" } else { "border-black" }]} />
+        {toggle("Regular expression", ".*", query.regex, SearchQuery { regex: !query.regex, ..query.clone() })}
+        {toggle("Match case", "Aa", query.case_sensitive, SearchQuery { case_sensitive: !query.case_sensitive, ..query.clone() })}
+        {toggle("Match whole word", "ab", query.whole_word, SearchQuery { whole_word: !query.whole_word, ..query.clone() })}
+        <span class={classes!["mx-2", if props.error.is_some() { "text-red-500" } else { "text-gray-500" }]}>
+            {if let Some(error) = &props.error {
+                error.clone()
+            } else if props.query.is_empty() {
+                String::new()
+            } else {
+                format!("{} of {}", props.current.map(|c| c + 1).unwrap_or(0), props.total)
+            }}
+        </span>
+        <button onclick={onclick_previous} disabled={props.total == 0} class={button.clone()}>{ "<" }</button>
+        <button onclick={onclick_next} disabled={props.total == 0} class={button}>{ ">" }</button>
+    </div>}
+}
@@ -0,0 +1,62 @@
+use gloo::events::EventListener;
+use wasm_bindgen::JsCast;
+use web_sys::{HtmlElement, KeyboardEvent};
+use yew::{function_component, html, use_effect_with, Callback, Html, Properties};
+
+use crate::actions::Action;
+
+fn focused(event: &KeyboardEvent) -> Option<HtmlElement> {
+    event
+        .target()
+        .and_then(|target| target.dyn_into::<HtmlElement>().ok())
+}
+
+/// Whether a key press is meant for a text field rather than for the viewer.
+fn is_typing(event: &KeyboardEvent) -> bool {
+    focused(event).is_some_and(|element| {
+        element.is_content_editable()
+            || matches!(element.tag_name().as_str(), "INPUT" | "TEXTAREA" | "SELECT")
+    })
+}
+
+/// Whether Enter and Space activate the focused element, like a button or a link.
+fn is_activatable(event: &KeyboardEvent) -> bool {
+    focused(event)
+        .is_some_and(|element| matches!(element.tag_name().as_str(), "BUTTON" | "A" | "SUMMARY"))
+}
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct ShortcutsProps {
+    pub(crate) on_action: Callback<Action>,
+}
+
+/// Listens for key presses anywhere on the page.
+#[function_component(Shortcuts)]
+pub fn shortcuts(props: &ShortcutsProps) -> Html {
+    use_effect_with(props.on_action.clone(), |on_action| {
+        let on_action = on_action.clone();
+        let listener = EventListener::new(&gloo::utils::document(), "keydown", move |event| {
+            let event = event.dyn_ref::<KeyboardEvent>().unwrap();
+            let modified = event.ctrl_key() || event.meta_key();
+            // The palette opens from anywhere, even while typing.
+            if modified && event.key() == "k" {
+                event.prevent_default();
+                on_action.emit(Action::OpenPalette);
+                return;
+            }
+            if modified || event.alt_key() || is_typing(event) {
+                return;
+            }
+            if matches!(event.key().as_str(), "Enter" | " ") && is_activatable(event) {
+                return;
+            }
+            if let Some(action) = Action::from_key(&event.key()) {
+                event.prevent_default();
+                on_action.emit(action);
+            }
+        });
+        move || drop(listener)
+    });
+
+    html! {}
+}
@@ -1,12 +1,40 @@
+use std::{fmt::Display, ops::Range};
+
 use yew::Properties;
 
-use crate::proto::{Event, Span};
+use crate::{
+    proto::{Event, Span},
+    timeline::TimeRange,
+};
 
 #[derive(Debug)]
 pub struct ParseError {
-    _line_no: usize,
-    _content: String,
-    _error: serde_json::Error,
+    /// Zero based index of the offending line.
+    pub line_no: usize,
+    pub content: String,
+    pub error: serde_json::Error,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line_no + 1, self.error)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// A line of the log that looked like an event but could not be parsed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SkippedLine {
+    /// Zero based index of the line.
+    pub line_no: usize,
+    pub error: String,
+}
+
+impl Display for SkippedLine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line_no + 1, self.error)
+    }
 }
 
 fn get_previous_span(event: &Event) -> Option<&Span> {
@@ -21,38 +49,81 @@ fn get_previous_span(event: &Event) -> Option<&Span> {
     }
 }
 
+fn extend_time(range: &mut Option<TimeRange>, time: Option<i64>) {
+    if let Some(time) = time {
+        let point = TimeRange::new(time, time);
+        *range = Some(range.map_or(point, |range| range.union(point)));
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Properties)]
 pub struct State {
     pub events: Vec<Event>,
     pub nodes: Vec<Node>,
+    /// For every event, the node whose children contain it, `None` if it is not part of the tree.
+    pub parents: Vec<Option<usize>>,
+    /// For every event, the zero based line of the log it was parsed from.
+    pub lines: Vec<usize>,
+    /// For every event, the byte range of its line in the log.
+    pub offsets: Vec<Range<usize>>,
+    /// Lines starting with `{` that failed to parse, in order.
+    pub skipped: Vec<SkippedLine>,
+    /// Time of the first event in microseconds since the Unix epoch.
+    pub start: Option<i64>,
 }
 
 impl State {
+    /// Parses a log with one JSON event per line.
+    ///
+    /// Lines that fail to parse are skipped and listed in [`State::skipped`], an error is only
+    /// returned if not a single event could be parsed.
     pub fn new(data: &str) -> Result<Self, ParseError> {
-        // TODO: Show lines had errors
-        let events: Result<Vec<Event>, _> = data
+        let mut events = vec![];
+        let mut lines = vec![];
+        let mut offsets = vec![];
+        let mut skipped = vec![];
+        let mut first_error = None;
+        for (line_no, line) in data
             .lines()
             .enumerate()
             .filter(|(_line_no, l)| l.starts_with('{'))
-            .map(|(line_no, line)| {
-                serde_json::from_str(line).map_err(|e| ParseError {
-                    _line_no: line_no,
-                    _content: line.to_string(),
-                    _error: e,
-                })
-            })
-            .filter(|r| r.is_ok())
-            .collect();
-
-        let events = events?;
+        {
+            match serde_json::from_str::<Event>(line) {
+                Ok(event) => {
+                    events.push(event);
+                    lines.push(line_no);
+                    let start = line.as_ptr() as usize - data.as_ptr() as usize;
+                    offsets.push(start..start + line.len());
+                }
+                Err(error) => {
+                    log::debug!("Failed to parse line {}: {}", line_no + 1, error);
+                    skipped.push(SkippedLine {
+                        line_no,
+                        error: error.to_string(),
+                    });
+                    first_error.get_or_insert(ParseError {
+                        line_no,
+                        content: line.to_string(),
+                        error,
+                    });
+                }
+            }
+        }
+
+        if let (true, Some(error)) = (events.is_empty(), first_error) {
+            return Err(error);
+        }
 
         log::debug!("{} events in log file", events.len());
 
         let mut nodes_storage: Vec<Node> = vec![Node {
             index: None,
+            parent: None,
             children: vec![],
             expanded: true,
+            time: None,
         }];
+        let mut parents = vec![None; events.len()];
 
         let mut tree: Vec<(usize, Option<&Span>)> = vec![(0, None)];
 
@@ -72,9 +143,12 @@ impl State {
 
                     let node = Node {
                         index: Some(index),
+                        parent: Some(current_node),
                         children: vec![],
                         expanded: false,
+                        time: None,
                     };
+                    parents[index] = Some(current_node);
 
                     nodes_storage.push(node);
 
@@ -102,6 +176,7 @@ impl State {
                         );
                     } else {
                         log::debug!("Exiting span {:?}", event.span.as_ref());
+                        extend_time(&mut nodes_storage[current_node].time, event.time());
                         let _ = tree.pop();
                     }
                 }
@@ -112,6 +187,7 @@ impl State {
                         nodes_storage[current_node]
                             .children
                             .push(EventType::Message(index));
+                        parents[index] = Some(current_node);
                     } else {
                         log::debug!(
                             "Ignoring event: {:?}, span {:?} does not match expected span {:?}",
@@ -124,21 +200,110 @@ impl State {
             }
         }
 
+        // Children are always stored after their parent, so going backwards every node
+        // is complete before it is added to its parent.
+        for node in (0..nodes_storage.len()).rev() {
+            let Node {
+                index,
+                children,
+                time,
+                parent,
+                ..
+            } = &nodes_storage[node];
+            let mut time = *time;
+            extend_time(&mut time, index.and_then(|index| events[index].time()));
+            for child in children {
+                if let EventType::Message(index) = child {
+                    extend_time(&mut time, events[*index].time());
+                }
+            }
+            let parent = *parent;
+            nodes_storage[node].time = time;
+            if let (Some(parent), Some(time)) = (parent, time) {
+                let parent_time = &mut nodes_storage[parent].time;
+                *parent_time = Some(parent_time.map_or(time, |parent| parent.union(time)));
+            }
+        }
+
         log::debug!("Processed all events");
 
         Ok(Self {
+            start: events.iter().find_map(|event| event.time()),
             events,
             nodes: nodes_storage,
+            parents,
+            lines,
+            offsets,
+            skipped,
         })
     }
+
+    /// Returns the line of `content`, the log the state was parsed from, holding the event.
+    pub fn raw_line<'a>(&self, content: &'a str, event: usize) -> Option<&'a str> {
+        self.offsets
+            .get(event)
+            .and_then(|range| content.get(range.clone()))
+    }
+
+    /// Returns the span nodes enclosing the row of the given event, innermost first.
+    pub fn ancestors(&self, event: usize) -> Vec<usize> {
+        let mut ancestors = vec![];
+        let mut node = self.parents.get(event).copied().flatten();
+        while let Some(index) = node {
+            if self.nodes[index].index.is_some() {
+                ancestors.push(index);
+            }
+            node = self.nodes[index].parent;
+        }
+        ancestors
+    }
+
+    /// Returns `true` if the event is a message row of the tree.
+    ///
+    /// Span lifecycle records are not, `enter` events are shown as the span header and the
+    /// others are not part of the tree at all.
+    pub fn is_message(&self, event: usize) -> bool {
+        self.parents.get(event).copied().flatten().is_some()
+            && self.events[event].fields.message != "enter"
+    }
+
+    /// Returns the span node entered by the given event, `None` if it is not a span header.
+    pub fn node_of(&self, event: usize) -> Option<usize> {
+        self.nodes.iter().position(|node| node.index == Some(event))
+    }
+
+    /// Returns the event indices of all rows of the tree in display order.
+    ///
+    /// Span nodes are represented by the index of their `enter` event.
+    pub fn rows(&self) -> Vec<usize> {
+        fn visit(state: &State, node: usize, rows: &mut Vec<usize>) {
+            for child in &state.nodes[node].children {
+                match child {
+                    EventType::Message(index) => rows.push(*index),
+                    EventType::Node(node) => {
+                        rows.extend(state.nodes[*node].index);
+                        visit(state, *node, rows);
+                    }
+                }
+            }
+        }
+
+        let mut rows = vec![];
+        visit(self, 0, &mut rows);
+        rows
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Node {
     pub index: Option<usize>,
+    /// Index of the enclosing node, `None` for the root node.
+    pub parent: Option<usize>,
     /// Indices of all child nodes
     pub children: Vec<EventType>,
     pub expanded: bool,
+    /// The time from entering the span to exiting it, including all events inside.
+    pub time: Option<TimeRange>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
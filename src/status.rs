@@ -0,0 +1,88 @@
+use wasm_bindgen::JsValue;
+use yew::{classes, function_component, html, Callback, Html, Properties};
+
+use crate::{
+    error::{LoadError, RateLimit},
+    gist::Progress,
+};
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct ErrorViewProps {
+    pub(crate) error: LoadError,
+    /// Shows a button to continue despite the error, if set.
+    #[prop_or_default]
+    pub(crate) on_dismiss: Option<Callback<()>>,
+}
+
+#[function_component(ErrorView)]
+pub fn error_view(props: &ErrorViewProps) -> Html {
+    let error = &props.error;
+
+    let hint = match error {
+        LoadError::InvalidParameter { .. } => {
+            html! {<p>{"Check the link for typos, or continue with the default settings."}</p>}
+        }
+        LoadError::Http {
+            rate_limit: Some(rate_limit),
+            ..
+        } if error.is_rate_limited() => rate_limit_hint(rate_limit),
+        LoadError::Http { status: 404, .. } => {
+            html! {<p>{"The gist does not exist or is not public."}</p>}
+        }
+        LoadError::Network(_) => {
+            html! {<p>{"Check your internet connection and reload the page."}</p>}
+        }
+        LoadError::Unparsable(_) | LoadError::SkippedLines { .. } => {
+            html! {<p>{"Logs must contain one JSON formatted tracing event per line."}</p>}
+        }
+        _ => html! {},
+    };
+
+    html! {<div class={classes!["p-3", "border", "border-red-500", "rounded-md", "bg-red-50"]}>
+        <p class="font-bold">{error.to_string()}</p>
+        {hint}
+        {if let Some(on_dismiss) = &props.on_dismiss {
+            let on_dismiss = on_dismiss.clone();
+            html!{<button onclick={move |_| on_dismiss.emit(())} class={classes!["mt-3", "px-2", "py-1", "border", "border-black", "bg-white"]}>{"Continue"}</button>}
+        } else {
+            html!{}
+        }}
+    </div>}
+}
+
+fn rate_limit_hint(rate_limit: &RateLimit) -> Html {
+    let reset = js_sys::Date::new(&JsValue::from_f64(rate_limit.reset as f64 * 1000.0));
+    let reset = String::from(reset.to_locale_time_string("default"));
+
+    html! {<p>
+        {format!(
+            "All {} requests per hour are used up, the limit resets at {reset}. Logs opened before are still available from the cache.",
+            rate_limit.limit,
+        )}
+    </p>}
+}
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct LoadingViewProps {
+    #[prop_or_default]
+    pub(crate) progress: Option<Progress>,
+}
+
+#[function_component(LoadingView)]
+pub fn loading_view(props: &LoadingViewProps) -> Html {
+    let text = props
+        .progress
+        .map(|progress| progress.to_string())
+        .unwrap_or_else(|| "Loading file ...".into());
+
+    html! {<div class="text-gray-500">
+        <p>{text}</p>
+        {if let Some(fraction) = props.progress.and_then(|progress| progress.fraction()) {
+            html!{<div class={classes!["mt-2", "w-64", "h-2", "rounded-md", "bg-gray-200"]}>
+                <div class={classes!["h-2", "rounded-md", "bg-blue-500"]} style={format!("width: {:.0}%", fraction * 100.0)}></div>
+            </div>}
+        } else {
+            html!{}
+        }}
+    </div>}
+}
@@ -0,0 +1,83 @@
+use wasm_bindgen::JsCast;
+use web_sys::HtmlSelectElement;
+use yew::{classes, function_component, html, Callback, Event, Html, Properties};
+
+use log_viewer::{
+    level_filter::{LevelFilter, Levels, LEVELS},
+    proto::log_level::LogLevel,
+    targets::TargetNode,
+};
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct TargetSidebarProps {
+    pub(crate) root: TargetNode,
+    pub(crate) level_filter: LevelFilter,
+    pub(crate) on_select: Callback<LevelFilter>,
+}
+
+#[function_component(TargetSidebar)]
+pub fn target_sidebar(props: &TargetSidebarProps) -> Html {
+    html! {<div class={classes!["w-96", "shrink-0", "m-3", "text-sm"]}>
+        <p class="font-bold">{"Targets"}</p>
+        <p class="text-gray-500">{format!("{} of {} events visible", props.root.visible, props.root.total())}</p>
+        {for props.root.children.iter().map(|node| target_node(props, node))}
+    </div>}
+}
+
+fn target_node(props: &TargetSidebarProps, node: &TargetNode) -> Html {
+    let target = Some(node.path.clone());
+    let levels = props.level_filter.levels(&target);
+
+    let onchange = {
+        let level_filter = props.level_filter.clone();
+        let on_select = props.on_select.clone();
+        let target = target.clone();
+        move |event: Event| {
+            // When events are created the target is undefined, it's only
+            // when dispatched does the target get added.
+            let event_target = event.target();
+            // Events can bubble so this listener might catch events from child
+            // elements which are not of type HtmlSelectElement
+            let select = event_target
+                .and_then(|t| t.dyn_into::<HtmlSelectElement>().ok())
+                .unwrap();
+            let level_filter = level_filter.clone();
+            on_select.emit(match select.value().parse::<LogLevel>() {
+                Ok(level) => level_filter.set_level(target.clone(), level),
+                Err(_) => level_filter.remove(&target),
+            });
+        }
+    };
+    let selected = |level: LogLevel| levels == Some(Levels::AtLeast(level));
+
+    let summary = html! {<span class="inline-flex items-center w-11/12">
+        <span class="grow truncate" title={node.path.clone()}>{&node.name}</span>
+        {for LEVELS.iter().zip(node.counts).filter(|(_, count)| *count > 0).map(|(level, count)| html!{
+            <span title={format!("{count} {level} events")} class={classes!["ml-1", "px-1", "rounded-md", format!("bg-{}", level.color())]}>{count}</span>
+        })}
+        <span title="hidden events" class={classes!["ml-1", "text-gray-500"]}>{format!("-{}", node.hidden())}</span>
+        <select {onchange} title="Level of the target" class={classes!["ml-1", "border", "border-black"]}>
+            <option value="inherit" selected={levels.is_none()}>{"inherit"}</option>
+            {for [LogLevel::None, LogLevel::Error, LogLevel::Warn, LogLevel::Info, LogLevel::Debug, LogLevel::Trace].into_iter().map(|level| html!{
+                <option value={level.to_string()} selected={selected(level)}>{level.to_string()}</option>
+            })}
+            {if let Some(Levels::Only(set)) = levels {
+                let label = set.iter().map(|level| level.to_string()).collect::<Vec<_>>().join("|");
+                html!{<option value="set" selected=true disabled=true>{label}</option>}
+            } else {
+                html!{}
+            }}
+        </select>
+    </span>};
+
+    if node.children.is_empty() {
+        html! {<div class="pl-4 mt-1">{summary}</div>}
+    } else {
+        html! {<details class="mt-1">
+            <summary class="cursor-pointer">{summary}</summary>
+            <div class="pl-3">
+                {for node.children.iter().map(|child| target_node(props, child))}
+            </div>
+        </details>}
+    }
+}
@@ -0,0 +1,91 @@
+use crate::{
+    context::{RowState, Visibility},
+    level_filter::LEVELS,
+    state::State,
+};
+
+/// A module in the hierarchy of event targets, with the events of it and its submodules.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct TargetNode {
+    /// The last segment of the path, e.g. `cmsisdap`.
+    pub name: String,
+    /// The full target, e.g. `probe_rs::probe::cmsisdap`.
+    pub path: String,
+    /// Number of events per level, in the order of [`LEVELS`].
+    pub counts: [usize; LEVELS.len()],
+    /// Number of events displayed with the current filters.
+    pub visible: usize,
+    /// Submodules, the ones with the most events first.
+    pub children: Vec<TargetNode>,
+}
+
+impl TargetNode {
+    /// Builds the target hierarchy of all message rows of `state`.
+    ///
+    /// The returned root node has an empty path and contains all events.
+    pub fn new(state: &State, visibility: &Visibility) -> Self {
+        let mut root = TargetNode::default();
+
+        for (index, event) in state.events.iter().enumerate() {
+            // Span headers are counted with the span, only messages are rows of their own.
+            if !state.is_message(index) {
+                continue;
+            }
+            let level = LEVELS.iter().position(|level| *level == event.level);
+            let visible = visibility.rows.get(index) != Some(&RowState::Hidden);
+
+            let mut node = &mut root;
+            node.add(level, visible);
+            for segment in event.target.split("::") {
+                let position = match node.children.iter().position(|child| child.name == segment) {
+                    Some(position) => position,
+                    None => {
+                        let path = if node.path.is_empty() {
+                            segment.to_string()
+                        } else {
+                            format!("{}::{segment}", node.path)
+                        };
+                        node.children.push(TargetNode {
+                            name: segment.into(),
+                            path,
+                            ..Default::default()
+                        });
+                        node.children.len() - 1
+                    }
+                };
+                node = &mut node.children[position];
+                node.add(level, visible);
+            }
+        }
+
+        root.sort();
+        root
+    }
+
+    fn add(&mut self, level: Option<usize>, visible: bool) {
+        if let Some(level) = level {
+            self.counts[level] += 1;
+        }
+        if visible {
+            self.visible += 1;
+        }
+    }
+
+    fn sort(&mut self) {
+        self.children
+            .sort_by(|a, b| b.total().cmp(&a.total()).then(a.name.cmp(&b.name)));
+        for child in &mut self.children {
+            child.sort();
+        }
+    }
+
+    /// Number of events of the target and its submodules.
+    pub fn total(&self) -> usize {
+        self.counts.iter().sum()
+    }
+
+    /// Number of events hidden by the current filters.
+    pub fn hidden(&self) -> usize {
+        self.total().saturating_sub(self.visible)
+    }
+}
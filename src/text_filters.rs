@@ -0,0 +1,82 @@
+use wasm_bindgen::JsCast;
+use web_sys::HtmlInputElement;
+use yew::{classes, function_component, html, use_state, Callback, Html, InputEvent, Properties};
+
+use log_viewer::search::{SearchQuery, TextFilter, TextFilterMode};
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct TextFiltersProps {
+    /// The current search, which the buttons turn into a filter.
+    pub(crate) search: SearchQuery,
+    pub(crate) filters: Vec<TextFilter>,
+    pub(crate) on_change: Callback<Vec<TextFilter>>,
+}
+
+#[function_component(TextFilters)]
+pub fn text_filters(props: &TextFiltersProps) -> Html {
+    let target = use_state(String::new);
+
+    let oninput = {
+        let target = target.clone();
+        move |event: InputEvent| {
+            // When events are created the target is undefined, it's only
+            // when dispatched does the target get added.
+            let event_target = event.target();
+            // Events can bubble so this listener might catch events from child
+            // elements which are not of type HtmlInputElement
+            let input = event_target
+                .and_then(|t| t.dyn_into::<HtmlInputElement>().ok())
+                .unwrap();
+            target.set(input.value());
+        }
+    };
+
+    let add = |mode: TextFilterMode| {
+        let filters = props.filters.clone();
+        let on_change = props.on_change.clone();
+        let search = props.search.clone();
+        let target = target.clone();
+        move |_| {
+            let target = target.trim();
+            let mut filters = filters.clone();
+            filters.push(TextFilter {
+                query: search.clone(),
+                mode,
+                target: (!target.is_empty()).then(|| target.to_string()),
+                message_only: false,
+            });
+            on_change.emit(filters);
+        }
+    };
+
+    let invalid = props.search.is_empty() || props.search.matcher().is_err();
+    let button = classes![
+        "ml-1",
+        "px-2",
+        "py-1",
+        "border",
+        "border-black",
+        "disabled:text-gray-400"
+    ];
+
+    html! {<div class="flex flex-wrap items-center ml-3 my-3">
+        <button onclick={add(TextFilterMode::Only)} disabled={invalid} class={button.clone()}>{"Only show matching"}</button>
+        <button onclick={add(TextFilterMode::Hide)} disabled={invalid} class={button}>{"Hide matching"}</button>
+        <input {oninput} value={(*target).clone()} placeholder="in target (optional)" class={classes!["ml-1", "px-2", "py-1", "border", "border-black"]} />
+        {for props.filters.iter().enumerate().map(|(i, filter)| {
+            let onclick = {
+                let filters = props.filters.clone();
+                let on_change = props.on_change.clone();
+                move |_| {
+                    let mut filters = filters.clone();
+                    filters.remove(i);
+                    on_change.emit(filters);
+                }
+            };
+            html!{<span class={classes!["ml-2", "px-2", "py-1", "rounded-lg", "bg-gray-200"]}>
+                {filter.to_string()}
+                <button {onclick} title="Remove filter" class="ml-2">{"×"}</button>
+            </span>}
+        })}
+    </div>}
+}
@@ -0,0 +1,26 @@
+use gloo::storage::{LocalStorage, Storage};
+
+/// `localStorage` key of whether the dark theme is on.
+const THEME_KEY: &str = "dark-theme";
+
+/// Inverts the page instead of restyling every component, hues are turned back so the
+/// level colors stay recognizable.
+const DARK_STYLE: &str = "filter: invert(1) hue-rotate(180deg); background: white;";
+
+/// Applies the stored theme to the page.
+pub fn apply_theme() {
+    let dark = LocalStorage::get::<bool>(THEME_KEY).unwrap_or_default();
+    let style = if dark { DARK_STYLE } else { "" };
+    if let Err(error) = gloo::utils::document_element().set_attribute("style", style) {
+        log::warn!("Failed to apply the theme: {error:?}");
+    }
+}
+
+/// Switches between the light and dark theme and remembers the choice.
+pub fn toggle_theme() {
+    let dark = LocalStorage::get::<bool>(THEME_KEY).unwrap_or_default();
+    if let Err(error) = LocalStorage::set(THEME_KEY, !dark) {
+        log::warn!("Failed to store the theme: {error}");
+    }
+    apply_theme();
+}
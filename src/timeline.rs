@@ -0,0 +1,78 @@
+use crate::state::State;
+
+/// A time range in microseconds since the Unix epoch, both ends inclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TimeRange {
+    pub start: i64,
+    pub end: i64,
+}
+
+impl TimeRange {
+    /// Creates a range from two times in any order.
+    pub fn new(a: i64, b: i64) -> Self {
+        Self {
+            start: a.min(b),
+            end: a.max(b),
+        }
+    }
+
+    pub fn contains(&self, time: i64) -> bool {
+        self.start <= time && time <= self.end
+    }
+
+    pub fn overlaps(&self, other: &TimeRange) -> bool {
+        self.start <= other.end && other.start <= self.end
+    }
+
+    /// Returns the smallest range containing both ranges.
+    pub fn union(self, other: TimeRange) -> Self {
+        Self {
+            start: self.start.min(other.start),
+            end: self.end.max(other.end),
+        }
+    }
+}
+
+/// The number of events over time, for drawing a timeline.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Density {
+    /// The time covered by all events.
+    pub range: TimeRange,
+    /// Events per bucket, the buckets split the range evenly.
+    pub counts: Vec<usize>,
+}
+
+impl Density {
+    /// Counts the events of `state` in `buckets` buckets, `None` if no event has a timestamp.
+    pub fn new(state: &State, buckets: usize) -> Option<Self> {
+        let times = state
+            .events
+            .iter()
+            .filter_map(|event| event.time())
+            .collect::<Vec<_>>();
+        let range = TimeRange::new(*times.iter().min()?, *times.iter().max()?);
+
+        let buckets = buckets.max(1);
+        let mut density = Self {
+            range,
+            counts: vec![0; buckets],
+        };
+        for time in times {
+            let bucket = (density.fraction_of(time) * buckets as f64) as usize;
+            density.counts[bucket.min(buckets - 1)] += 1;
+        }
+        Some(density)
+    }
+
+    /// Returns the time at `fraction` of the range, clamped to the range.
+    pub fn time_at(&self, fraction: f64) -> i64 {
+        let duration = (self.range.end - self.range.start) as f64;
+        self.range.start + (fraction.clamp(0.0, 1.0) * duration).round() as i64
+    }
+
+    /// Returns where `time` is in the range, from 0 to 1.
+    pub fn fraction_of(&self, time: i64) -> f64 {
+        let duration = (self.range.end - self.range.start).max(1) as f64;
+        ((time - self.range.start) as f64 / duration).clamp(0.0, 1.0)
+    }
+}
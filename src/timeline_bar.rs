@@ -0,0 +1,145 @@
+use std::rc::Rc;
+
+use web_sys::{HtmlElement, MouseEvent};
+use yew::{
+    classes, function_component, html, use_memo, use_node_ref, use_state, Callback, Html,
+    Properties,
+};
+
+use log_viewer::{
+    state::State,
+    timeline::{Density, TimeRange},
+    timestamp::format_duration,
+};
+
+/// Number of bars of the timeline.
+const BUCKETS: usize = 200;
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct TimelineProps {
+    pub(crate) state: Rc<State>,
+    pub(crate) range: Option<TimeRange>,
+    pub(crate) on_select: Callback<Option<TimeRange>>,
+}
+
+#[function_component(Timeline)]
+pub fn timeline(props: &TimelineProps) -> Html {
+    let density = use_memo(Rc::as_ptr(&props.state) as usize, {
+        let state = props.state.clone();
+        move |_| Density::new(&state, BUCKETS)
+    });
+    let strip = use_node_ref();
+    // Start and end of the current drag, as fractions of the timeline width.
+    let drag = use_state(|| None::<(f64, f64)>);
+
+    let Some(density) = (*density).clone() else {
+        return html! {};
+    };
+
+    let fraction = {
+        let strip = strip.clone();
+        move |event: &MouseEvent| {
+            let Some(strip) = strip.cast::<HtmlElement>() else {
+                return 0.0;
+            };
+            let rect = strip.get_bounding_client_rect();
+            ((event.client_x() as f64 - rect.left()) / rect.width().max(1.0)).clamp(0.0, 1.0)
+        }
+    };
+
+    let onmousedown = {
+        let drag = drag.clone();
+        let fraction = fraction.clone();
+        move |event: MouseEvent| {
+            event.prevent_default();
+            let at = fraction(&event);
+            drag.set(Some((at, at)));
+        }
+    };
+    let onmousemove = {
+        let drag = drag.clone();
+        let fraction = fraction.clone();
+        move |event: MouseEvent| {
+            if let Some((start, _)) = *drag {
+                drag.set(Some((start, fraction(&event))));
+            }
+        }
+    };
+    let finish = {
+        let drag = drag.clone();
+        let on_select = props.on_select.clone();
+        let density = density.clone();
+        move |event: MouseEvent| {
+            let Some((start, _)) = *drag else {
+                return;
+            };
+            let end = fraction(&event);
+            drag.set(None);
+            // A click without dragging clears the selection.
+            if (end - start).abs() < 0.002 {
+                on_select.emit(None);
+            } else {
+                on_select.emit(Some(TimeRange::new(
+                    density.time_at(start),
+                    density.time_at(end),
+                )));
+            }
+        }
+    };
+    let onclear = {
+        let on_select = props.on_select.clone();
+        move |_| on_select.emit(None)
+    };
+
+    let selection = match (*drag, props.range) {
+        (Some((start, end)), _) => Some((start.min(end), start.max(end))),
+        (None, Some(range)) => Some((
+            density.fraction_of(range.start),
+            density.fraction_of(range.end),
+        )),
+        (None, None) => None,
+    };
+    let max = density.counts.iter().copied().max().unwrap_or(1).max(1);
+    let relative = |time: i64| format_duration(time - density.range.start);
+
+    html! {<div class={classes!["mx-3", "mb-3"]}>
+        <div
+            ref={strip}
+            {onmousedown}
+            {onmousemove}
+            onmouseup={finish.clone()}
+            onmouseleave={finish}
+            class={classes!["relative", "h-12", "border", "border-black", "cursor-crosshair", "select-none"]}
+        >
+            <svg viewBox={format!("0 0 {} 100", density.counts.len())} preserveAspectRatio="none" class="w-full h-full">
+                {for density.counts.iter().enumerate().filter(|(_, count)| **count > 0).map(|(i, count)| {
+                    // At least a sliver, so single events don't disappear next to busy phases.
+                    let height = (*count as f64 / max as f64 * 100.0).max(4.0);
+                    html!{<rect x={i.to_string()} y={(100.0 - height).to_string()} width="1" height={height.to_string()} class="fill-gray-500" />}
+                })}
+            </svg>
+            {if let Some((start, end)) = selection {
+                html!{<div
+                    class={classes!["absolute", "top-0", "h-full", "bg-blue-300", "opacity-50", "pointer-events-none"]}
+                    style={format!("left: {:.2}%; width: {:.2}%", start * 100.0, (end - start) * 100.0)}
+                ></div>}
+            } else {
+                html!{}
+            }}
+        </div>
+        <div class={classes!["flex", "text-sm", "text-gray-500"]}>
+            <span>{relative(density.range.start)}</span>
+            <span class="grow text-center">
+                {if let Some(range) = props.range {
+                    html!{<>
+                        {format!("Showing {} to {}", relative(range.start), relative(range.end))}
+                        <button onclick={onclear} class={classes!["ml-2", "px-1", "border", "border-black", "text-black"]}>{"Clear"}</button>
+                    </>}
+                } else {
+                    html!{"Drag to select a time range"}
+                }}
+            </span>
+            <span>{relative(density.range.end)}</span>
+        </div>
+    </div>}
+}
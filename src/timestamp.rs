@@ -0,0 +1,69 @@
+/// Parses an RFC 3339 timestamp as written by `tracing-subscriber`, e.g. `2022-12-12T20:53:20.083463Z`,
+/// into microseconds since the Unix epoch.
+pub fn parse_timestamp(timestamp: &str) -> Option<i64> {
+    let (date, time) = timestamp.split_once(['T', ' '])?;
+
+    let mut date = date.splitn(3, '-');
+    let year: i64 = date.next()?.parse().ok()?;
+    let month: i64 = date.next()?.parse().ok()?;
+    let day: i64 = date.next()?.parse().ok()?;
+
+    let (time, offset) = if let Some(time) = time.strip_suffix(['Z', 'z']) {
+        (time, 0)
+    } else {
+        let split = time.rfind(['+', '-'])?;
+        let (time, offset) = time.split_at(split);
+        let sign = if offset.starts_with('-') { -1 } else { 1 };
+        let (hours, minutes) = offset[1..].split_once(':')?;
+        let offset = hours.parse::<i64>().ok()? * 3600 + minutes.parse::<i64>().ok()? * 60;
+        (time, sign * offset)
+    };
+
+    let (time, fraction) = time.split_once('.').unwrap_or((time, ""));
+    let mut time = time.splitn(3, ':');
+    let hours: i64 = time.next()?.parse().ok()?;
+    let minutes: i64 = time.next()?.parse().ok()?;
+    let seconds: i64 = time.next()?.parse().ok()?;
+
+    let micros = if fraction.is_empty() {
+        0
+    } else {
+        // Checked first, slicing bytes would panic on multi-byte characters.
+        if !fraction.bytes().all(|byte| byte.is_ascii_digit()) {
+            return None;
+        }
+        let digits = &fraction[..fraction.len().min(6)];
+        digits.parse::<i64>().ok()? * 10i64.pow(6 - digits.len() as u32)
+    };
+
+    let seconds =
+        days_from_civil(year, month, day) * 86400 + hours * 3600 + minutes * 60 + seconds - offset;
+    Some(seconds * 1_000_000 + micros)
+}
+
+/// Number of days since 1970-01-01 for the given proleptic Gregorian date.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = (month + 9) % 12;
+    let day_of_year = (153 * month + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+/// Formats a duration given in microseconds, e.g. `+1.234567s`.
+pub fn format_duration(micros: i64) -> String {
+    let sign = if micros < 0 { '-' } else { '+' };
+    let micros = micros.unsigned_abs();
+    format!("{sign}{}.{:06}s", micros / 1_000_000, micros % 1_000_000)
+}
+
+/// Returns the time of day of an RFC 3339 timestamp, e.g. `20:53:20.083463`.
+pub fn time_of_day(timestamp: &str) -> &str {
+    let time = timestamp
+        .split_once(['T', ' '])
+        .map(|(_, time)| time)
+        .unwrap_or(timestamp);
+    time.trim_end_matches(['Z', 'z'])
+}
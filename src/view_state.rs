@@ -0,0 +1,532 @@
+use std::{collections::BTreeSet, fmt::Display, str::FromStr};
+
+use crate::{
+    facets::{show_fields, FieldFilter, FieldFilterMode},
+    level_filter::LevelFilter,
+    proto::{log_level::LogLevel, Event},
+    query::Query,
+    search::{SearchQuery, TextFilter, TextFilterMode},
+    state::Node,
+    timeline::TimeRange,
+    timestamp::{format_duration, time_of_day},
+};
+
+/// Version of the URL encoding written by [`ViewState::to_query`].
+const VERSION: u32 = 2;
+
+/// Everything needed to reproduce what a user sees, encoded in the page URL.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ViewState {
+    pub source: Option<Source>,
+    pub filter: LevelFilter,
+    pub search: SearchQuery,
+    /// Searches turned into filters, applied in addition to the level filter.
+    pub text_filters: Vec<TextFilter>,
+    /// Events must have, or must not have, these field values.
+    pub field_filters: Vec<FieldFilter>,
+    /// A filter in the [query language](crate::query), invalid queries don't filter anything.
+    pub query: String,
+    /// Only events in this range, and spans overlapping it, are shown.
+    pub time_range: Option<TimeRange>,
+    /// Number of events around each shown event which are shown as context, 0 to disable.
+    pub context: usize,
+    /// Hidden events revealed with "show more".
+    pub revealed: BTreeSet<usize>,
+    /// Indices of the expanded span nodes.
+    pub expanded: BTreeSet<usize>,
+    /// Index of the selected event.
+    pub selected: Option<usize>,
+    pub time_mode: TimeMode,
+}
+
+/// Where the log was loaded from.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Source {
+    pub gist: String,
+    /// The pinned gist revision, `None` for the latest one.
+    pub revision: Option<String>,
+}
+
+/// How event timestamps are displayed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimeMode {
+    #[default]
+    Hidden,
+    /// The time of day of the event.
+    Absolute,
+    /// The time since the first event of the log.
+    Relative,
+}
+
+impl TimeMode {
+    /// Returns the next mode, for toggling through all of them.
+    pub fn next(self) -> Self {
+        match self {
+            TimeMode::Hidden => TimeMode::Absolute,
+            TimeMode::Absolute => TimeMode::Relative,
+            TimeMode::Relative => TimeMode::Hidden,
+        }
+    }
+
+    /// Formats `timestamp` for display, `start` is the time of the first event of the log.
+    pub fn format(self, timestamp: Option<&str>, start: Option<i64>) -> Option<String> {
+        let timestamp = timestamp?;
+        match self {
+            TimeMode::Hidden => None,
+            TimeMode::Absolute => Some(time_of_day(timestamp).into()),
+            TimeMode::Relative => {
+                let time = crate::timestamp::parse_timestamp(timestamp)?;
+                Some(format_duration(time - start?))
+            }
+        }
+    }
+
+    fn key(self) -> &'static str {
+        match self {
+            TimeMode::Hidden => "h",
+            TimeMode::Absolute => "a",
+            TimeMode::Relative => "r",
+        }
+    }
+}
+
+impl Display for TimeMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TimeMode::Hidden => write!(f, "hidden"),
+            TimeMode::Absolute => write!(f, "absolute"),
+            TimeMode::Relative => write!(f, "relative"),
+        }
+    }
+}
+
+/// A query parameter that could not be decoded.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ViewStateError {
+    pub key: String,
+    pub value: String,
+    pub reason: String,
+}
+
+impl ViewStateError {
+    fn new(key: &str, value: &str, reason: impl Display) -> Self {
+        Self {
+            key: key.into(),
+            value: value.into(),
+            reason: reason.to_string(),
+        }
+    }
+}
+
+impl Display for ViewStateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}={}: {}", self.key, self.value, self.reason)
+    }
+}
+
+impl std::error::Error for ViewStateError {}
+
+impl ViewState {
+    /// Returns `true` if `event` passes the filters of the view.
+    pub fn shows(&self, event: &Event) -> bool {
+        self.time_range
+            .is_none_or(|range| event.time().is_none_or(|time| range.contains(time)))
+            && self.filter.show_event(event)
+            && self.text_filters.iter().all(|filter| filter.show(event))
+            && show_fields(&self.field_filters, event)
+            && match Query::parse_cached(&self.query) {
+                Ok(Some(query)) => query.matches(event),
+                _ => true,
+            }
+    }
+
+    /// Returns `true` if the span of `node` overlaps the time range of the view.
+    pub fn shows_span(&self, node: &Node) -> bool {
+        self.time_range
+            .is_none_or(|range| node.time.is_none_or(|time| range.overlaps(&time)))
+    }
+
+    /// Encodes the state as a URL query string, without the leading `?`.
+    ///
+    /// Values equal to their default are left out to keep links short.
+    pub fn to_query(&self) -> String {
+        let mut pairs = vec![("v".to_string(), VERSION.to_string())];
+
+        if let Some(source) = &self.source {
+            pairs.push(("gist".into(), source.gist.clone()));
+            if let Some(revision) = &source.revision {
+                pairs.push(("rev".into(), revision.clone()));
+            }
+        }
+
+        pairs.push(("f".into(), self.filter.to_string()));
+
+        if !self.search.is_empty() {
+            pairs.push(("q".into(), self.search.pattern.clone()));
+            let flags = self.search.flags();
+            if !flags.is_empty() {
+                pairs.push(("qo".into(), flags));
+            }
+        }
+        for filter in &self.text_filters {
+            pairs.push(("tf".into(), encode_text_filter(filter)));
+        }
+        for filter in &self.field_filters {
+            pairs.push(("ff".into(), encode_field_filter(filter)));
+        }
+        if !self.query.is_empty() {
+            pairs.push(("fq".into(), self.query.clone()));
+        }
+        if let Some(range) = self.time_range {
+            pairs.push(("tr".into(), format!("{}-{}", range.start, range.end)));
+        }
+        if self.context != 0 {
+            pairs.push(("c".into(), self.context.to_string()));
+        }
+        if !self.revealed.is_empty() {
+            pairs.push(("rv".into(), encode_ranges(&self.revealed)));
+        }
+        if !self.expanded.is_empty() {
+            pairs.push(("x".into(), encode_ranges(&self.expanded)));
+        }
+        if let Some(selected) = self.selected {
+            pairs.push(("e".into(), selected.to_string()));
+        }
+        if self.time_mode != TimeMode::default() {
+            pairs.push(("t".into(), self.time_mode.key().into()));
+        }
+
+        pairs
+            .iter()
+            .map(|(key, value)| format!("{}={}", encode_component(key), encode_component(value)))
+            .collect::<Vec<_>>()
+            .join("&")
+    }
+
+    /// Decodes a URL query string, with or without the leading `?`.
+    ///
+    /// Links without a version use the legacy `filter-<target>=<level>` keys, version 1 links
+    /// the `f.<target>=<level>` keys which version 2 replaced with `RUST_LOG` directives.
+    pub fn from_query(query: &str) -> Result<Self, ViewStateError> {
+        let pairs = query
+            .trim_start_matches('?')
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+                let key = decode_component(key, key)?;
+                let value = decode_component(&key, value)?;
+                Ok((key, value))
+            })
+            .collect::<Result<Vec<_>, ViewStateError>>()?;
+
+        let version = match pairs.iter().find(|(key, _)| key == "v") {
+            Some((key, value)) => value
+                .parse::<u32>()
+                .map_err(|e| ViewStateError::new(key, value, e))?,
+            None => 0,
+        };
+        if version > VERSION {
+            return Err(ViewStateError::new(
+                "v",
+                &version.to_string(),
+                "the link was created by a newer version of the viewer",
+            ));
+        }
+
+        let mut state = ViewState::default();
+        let mut filter = LevelFilter::default();
+
+        for (key, value) in &pairs {
+            let key = key.as_str();
+            match (version, key) {
+                (_, "v") => {}
+                (_, "gist") => {
+                    if value.is_empty() {
+                        return Err(ViewStateError::new(key, value, "the gist id is empty"));
+                    }
+                    let revision = state.source.take().and_then(|source| source.revision);
+                    state.source = Some(Source {
+                        gist: value.clone(),
+                        revision,
+                    });
+                }
+                (_, "rev") => {
+                    // `rev` may come before `gist`, the gist id is filled in once it is seen.
+                    let source = state.source.get_or_insert_with(|| Source {
+                        gist: String::new(),
+                        revision: None,
+                    });
+                    source.revision = Some(value.clone());
+                }
+                (0, key) if key.starts_with("filter") => {
+                    let target = key.replace('-', "::").replace("filter::", "");
+                    let target = if target != "filter" {
+                        Some(target)
+                    } else {
+                        None
+                    };
+                    filter = filter.set_level(target, parse_level(key, value)?);
+                }
+                (1, "f") => filter = filter.set_level(None, parse_level(key, value)?),
+                (1, key) if key.starts_with("f.") => {
+                    filter = filter.set_level(Some(key[2..].into()), parse_level(key, value)?)
+                }
+                (2.., "f") => {
+                    filter = LevelFilter::from_directives(value)
+                        .map_err(|e| ViewStateError::new(key, value, e))?
+                }
+                (1.., "q") => state.search.pattern = value.clone(),
+                (1.., "qo") => {
+                    state.search = std::mem::take(&mut state.search)
+                        .with_flags(value)
+                        .map_err(|e| ViewStateError::new(key, value, e))?
+                }
+                (1.., "tf") => state.text_filters.push(decode_text_filter(key, value)?),
+                (1.., "ff") => state.field_filters.push(decode_field_filter(key, value)?),
+                (1.., "fq") => state.query = value.clone(),
+                (1.., "tr") => {
+                    let range = value.split_once('-').and_then(|(start, end)| {
+                        Some(TimeRange::new(start.parse().ok()?, end.parse().ok()?))
+                    });
+                    state.time_range =
+                        Some(range.ok_or_else(|| {
+                            ViewStateError::new(key, value, "expected start-end")
+                        })?);
+                }
+                (1.., "c") => {
+                    state.context = value
+                        .parse()
+                        .map_err(|e| ViewStateError::new(key, value, e))?
+                }
+                (1.., "rv") => state.revealed = decode_ranges(key, value)?,
+                (1.., "x") => state.expanded = decode_ranges(key, value)?,
+                (1.., "e") => {
+                    state.selected = Some(
+                        value
+                            .parse()
+                            .map_err(|e| ViewStateError::new(key, value, e))?,
+                    )
+                }
+                (1.., "t") => {
+                    state.time_mode = match value.as_str() {
+                        "h" => TimeMode::Hidden,
+                        "a" => TimeMode::Absolute,
+                        "r" => TimeMode::Relative,
+                        _ => return Err(ViewStateError::new(key, value, "unknown time mode")),
+                    }
+                }
+                _ => log::debug!("Ignoring unknown query parameter {key}"),
+            }
+        }
+
+        // Only `rev` creates a source without a gist, an empty `gist` is rejected above.
+        if let Some(Source {
+            gist,
+            revision: Some(revision),
+        }) = &state.source
+        {
+            if gist.is_empty() {
+                return Err(ViewStateError::new(
+                    "rev",
+                    revision,
+                    "a revision requires a gist",
+                ));
+            }
+        }
+
+        state.filter = filter;
+        Ok(state)
+    }
+}
+
+fn parse_level(key: &str, value: &str) -> Result<LogLevel, ViewStateError> {
+    LogLevel::from_str(value).map_err(|e| ViewStateError::new(key, value, e))
+}
+
+/// Encodes a text filter as `<mode><flags>/<target>/<pattern>`, e.g. `hr/probe_rs/^Transmit`.
+///
+/// Filters matching only the message have an `m` right after the mode. The pattern comes last,
+/// so it may contain `/` itself.
+fn encode_text_filter(filter: &TextFilter) -> String {
+    let mode = match filter.mode {
+        TextFilterMode::Only => 'o',
+        TextFilterMode::Hide => 'h',
+    };
+    let scope = if filter.message_only { "m" } else { "" };
+    format!(
+        "{mode}{scope}{}/{}/{}",
+        filter.query.flags(),
+        filter.target.as_deref().unwrap_or_default(),
+        filter.query.pattern
+    )
+}
+
+fn decode_text_filter(key: &str, value: &str) -> Result<TextFilter, ViewStateError> {
+    let mut parts = value.splitn(3, '/');
+    let (Some(options), Some(target), Some(pattern)) = (parts.next(), parts.next(), parts.next())
+    else {
+        return Err(ViewStateError::new(
+            key,
+            value,
+            "expected mode/target/pattern",
+        ));
+    };
+
+    let mode = match options.chars().next() {
+        Some('o') => TextFilterMode::Only,
+        Some('h') => TextFilterMode::Hide,
+        _ => return Err(ViewStateError::new(key, value, "unknown text filter mode")),
+    };
+    let flags = &options[1..];
+    let (message_only, flags) = match flags.strip_prefix('m') {
+        Some(flags) => (true, flags),
+        None => (false, flags),
+    };
+    let query = SearchQuery::new(pattern)
+        .with_flags(flags)
+        .map_err(|e| ViewStateError::new(key, value, e))?;
+
+    Ok(TextFilter {
+        query,
+        mode,
+        target: (!target.is_empty()).then(|| target.to_string()),
+        message_only,
+    })
+}
+
+/// Encodes a field filter as `<mode>/<name>/<value>`, e.g. `i/core/0`.
+fn encode_field_filter(filter: &FieldFilter) -> String {
+    let mode = match filter.mode {
+        FieldFilterMode::Include => 'i',
+        FieldFilterMode::Exclude => 'e',
+    };
+    format!("{mode}/{}/{}", filter.name, filter.value)
+}
+
+fn decode_field_filter(key: &str, value: &str) -> Result<FieldFilter, ViewStateError> {
+    let mut parts = value.splitn(3, '/');
+    let (Some(mode), Some(name), Some(field_value)) = (parts.next(), parts.next(), parts.next())
+    else {
+        return Err(ViewStateError::new(key, value, "expected mode/name/value"));
+    };
+
+    let mode = match mode {
+        "i" => FieldFilterMode::Include,
+        "e" => FieldFilterMode::Exclude,
+        _ => return Err(ViewStateError::new(key, value, "unknown field filter mode")),
+    };
+    Ok(FieldFilter {
+        name: name.into(),
+        value: field_value.into(),
+        mode,
+    })
+}
+
+/// Upper limit for the indices of a link, crafted links could otherwise list billions.
+const MAX_RANGE_INDICES: usize = 1 << 20;
+
+/// Encodes a set of indices as `.` separated ranges, e.g. `1-4.7`.
+fn encode_ranges(indices: &BTreeSet<usize>) -> String {
+    let mut ranges: Vec<(usize, usize)> = vec![];
+    for &index in indices {
+        match ranges.last_mut() {
+            Some((_, end)) if *end + 1 == index => *end = index,
+            _ => ranges.push((index, index)),
+        }
+    }
+
+    ranges
+        .iter()
+        .map(|(start, end)| {
+            if start == end {
+                start.to_string()
+            } else {
+                format!("{start}-{end}")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+fn decode_ranges(key: &str, value: &str) -> Result<BTreeSet<usize>, ViewStateError> {
+    let mut ranges = vec![];
+    let mut total = 0usize;
+    for range in value.split('.').filter(|range| !range.is_empty()) {
+        let (start, end) = range.split_once('-').unwrap_or((range, range));
+        let start: usize = start
+            .parse()
+            .map_err(|e| ViewStateError::new(key, value, e))?;
+        let end: usize = end
+            .parse()
+            .map_err(|e| ViewStateError::new(key, value, e))?;
+        if start > end {
+            return Err(ViewStateError::new(
+                key,
+                value,
+                "range ends before it starts",
+            ));
+        }
+        // Links are decoded before the log is loaded, so the number of events is not known.
+        total = total.saturating_add(end - start).saturating_add(1);
+        if total > MAX_RANGE_INDICES {
+            return Err(ViewStateError::new(
+                key,
+                value,
+                format!("more than {MAX_RANGE_INDICES} indices"),
+            ));
+        }
+        ranges.push(start..=end);
+    }
+    Ok(ranges.into_iter().flatten().collect())
+}
+
+/// Percent encodes everything but the characters that are safe inside a query component.
+///
+/// `=` is kept too, keys never contain one and values are split off at the first one.
+fn encode_component(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z'
+            | b'a'..=b'z'
+            | b'0'..=b'9'
+            | b'-'
+            | b'.'
+            | b'_'
+            | b'~'
+            | b':'
+            | b','
+            | b'=' => encoded.push(byte as char),
+            byte => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
+}
+
+/// Decodes `value`, the percent encoded value of parameter `key`.
+fn decode_component(key: &str, value: &str) -> Result<String, ViewStateError> {
+    let mut decoded = Vec::with_capacity(value.len());
+    let mut bytes = value.bytes();
+    while let Some(byte) = bytes.next() {
+        match byte {
+            b'+' => decoded.push(b' '),
+            b'%' => {
+                let hex = [bytes.next(), bytes.next()];
+                let byte = match hex {
+                    [Some(high), Some(low)] => std::str::from_utf8(&[high, low])
+                        .ok()
+                        .and_then(|hex| u8::from_str_radix(hex, 16).ok()),
+                    _ => None,
+                };
+                decoded.push(
+                    byte.ok_or_else(|| {
+                        ViewStateError::new(key, value, "invalid percent encoding")
+                    })?,
+                );
+            }
+            byte => decoded.push(byte),
+        }
+    }
+    String::from_utf8(decoded).map_err(|e| ViewStateError::new(key, value, e))
+}
@@ -0,0 +1,98 @@
+use log_viewer::annotations::{Annotation, Annotations, ANNOTATIONS_FILE};
+use serde_json::{json, Value};
+
+/// Stands in for `api.github.com/gists/<id>`, applying updates like Github does.
+struct MockGist {
+    gist: Value,
+}
+
+impl MockGist {
+    fn new(log: &str) -> Self {
+        Self {
+            gist: json!({
+                "id": "abc",
+                "files": {"trace.json": {"filename": "trace.json", "content": log}},
+                "history": [{"version": "1"}],
+            }),
+        }
+    }
+
+    fn get(&self) -> Value {
+        self.gist.clone()
+    }
+
+    /// Handles `PATCH /gists/<id>`: listed files are created or replaced, the others kept.
+    fn patch(&mut self, body: &str) -> Value {
+        let body: Value = serde_json::from_str(body).unwrap();
+        for (name, file) in body["files"].as_object().unwrap() {
+            self.gist["files"][name] = json!({"filename": name, "content": file["content"]});
+        }
+        let version = self.gist["history"].as_array().unwrap().len() + 1;
+        self.gist["history"]
+            .as_array_mut()
+            .unwrap()
+            .insert(0, json!({"version": version.to_string()}));
+        self.gist.clone()
+    }
+}
+
+/// Publishes `annotation` the way the viewer does: fetch the gist, add to its annotations, update.
+fn publish(api: &mut MockGist, annotation: Annotation) -> Annotations {
+    let gist = api.get();
+    let (published, update) = Annotations::publish(
+        gist["files"][ANNOTATIONS_FILE]["content"].as_str(),
+        annotation,
+    )
+    .unwrap();
+
+    let gist = api.patch(&serde_json::to_string(&update).unwrap());
+    let stored =
+        Annotations::parse(gist["files"][ANNOTATIONS_FILE]["content"].as_str().unwrap()).unwrap();
+    assert_eq!(stored, published);
+    stored
+}
+
+fn annotation(event: usize, comment: &str, author: &str) -> Annotation {
+    Annotation {
+        event,
+        comment: comment.into(),
+        author: author.into(),
+        created_at: String::new(),
+    }
+}
+
+#[test]
+fn publishing_keeps_the_log_and_earlier_annotations() {
+    let log = r#"{"timestamp":"2024-01-01T00:00:00Z","level":"INFO","fields":{"message":"attach"},"target":"probe_rs"}"#;
+    let mut api = MockGist::new(log);
+
+    publish(&mut api, annotation(7, "SWD fault starts here", "alice"));
+    let annotations = publish(&mut api, annotation(3, "still fine", "bob"));
+
+    assert_eq!(api.get()["files"]["trace.json"]["content"], log);
+    assert_eq!(api.get()["history"].as_array().unwrap().len(), 3);
+    assert_eq!(
+        annotations.annotations,
+        [
+            annotation(3, "still fine", "bob"),
+            annotation(7, "SWD fault starts here", "alice")
+        ]
+    );
+}
+
+#[test]
+fn annotations_of_an_event_keep_their_order() {
+    let annotations = Annotations::default()
+        .with(annotation(5, "first", "alice"))
+        .with(annotation(2, "other event", "bob"))
+        .with(annotation(5, "reply", "bob"));
+
+    assert_eq!(
+        annotations.on(5),
+        [
+            annotation(5, "first", "alice"),
+            annotation(5, "reply", "bob")
+        ]
+    );
+    assert!(annotations.on(3).is_empty());
+}
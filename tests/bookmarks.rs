@@ -0,0 +1,57 @@
+use log_viewer::{
+    bookmarks::{log_identity, Bookmark, BookmarkExport, Bookmarks},
+    view_state::Source,
+};
+
+fn bookmark(event: usize, note: &str) -> Bookmark {
+    Bookmark {
+        event,
+        note: note.into(),
+    }
+}
+
+#[test]
+fn bookmarks_stay_ordered() {
+    let bookmarks = Bookmarks::default()
+        .toggle(12)
+        .toggle(3)
+        .set_note(7, "probe reset here".into())
+        .set_note(12, "first bad read here".into());
+
+    assert_eq!(
+        bookmarks.bookmarks,
+        [
+            bookmark(3, ""),
+            bookmark(7, "probe reset here"),
+            bookmark(12, "first bad read here")
+        ]
+    );
+    assert!(!bookmarks.toggle(7).contains(7));
+}
+
+#[test]
+fn imports_replace_notes() {
+    let bookmarks = Bookmarks::default().set_note(1, "old".into());
+    let json = r#"{"log":"gist/abc/123","bookmarks":[{"event":1,"note":"new"},{"event":4}]}"#;
+    let export: BookmarkExport = serde_json::from_str(json).unwrap();
+
+    let bookmarks = bookmarks.merge(export.bookmarks);
+    assert_eq!(bookmarks.bookmarks, [bookmark(1, "new"), bookmark(4, "")]);
+}
+
+#[test]
+fn logs_are_identified_by_revision_or_content() {
+    let pinned = Source {
+        gist: "abc".into(),
+        revision: Some("123".into()),
+    };
+    assert_eq!(log_identity(&pinned, "log"), "gist/abc/123");
+
+    let latest = Source {
+        revision: None,
+        ..pinned
+    };
+    assert_eq!(log_identity(&latest, "log"), log_identity(&latest, "log"));
+    assert_ne!(log_identity(&latest, "log"), log_identity(&latest, "log 2"));
+    assert!(log_identity(&latest, "log").starts_with("sha/"));
+}
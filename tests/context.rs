@@ -0,0 +1,86 @@
+use log_viewer::{
+    context::{around, RowState, Visibility},
+    level_filter::LevelFilter,
+    state::State,
+    view_state::ViewState,
+};
+
+fn log() -> String {
+    let mut lines = vec![];
+    for i in 0..10 {
+        let level = if i == 6 { "ERROR" } else { "TRACE" };
+        lines.push(format!(
+            r#"{{"level":"{level}","target":"probe_rs","fields":{{"message":"line {i}"}},"span":null,"spans":null}}"#
+        ));
+    }
+    lines.join("\n")
+}
+
+fn errors_only(context: usize) -> ViewState {
+    ViewState {
+        filter: LevelFilter::from_directives("error").unwrap(),
+        context,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn without_context_only_shown_events_are_displayed() {
+    let state = State::new(&log()).unwrap();
+    let visibility = Visibility::new(&state, &errors_only(0));
+
+    assert_eq!(visibility.rows[6], RowState::Shown);
+    assert_eq!(visibility.rows[5], RowState::Hidden);
+    assert!(visibility.gaps.is_empty());
+}
+
+#[test]
+fn context_reveals_neighbours_and_gaps() {
+    let state = State::new(&log()).unwrap();
+    let visibility = Visibility::new(&state, &errors_only(2));
+
+    let context = (0..10)
+        .filter(|i| visibility.rows[*i] == RowState::Context)
+        .collect::<Vec<_>>();
+    assert_eq!(context, [4, 5, 7, 8]);
+
+    // Before the match the gap reveals the events closest to it, after it the first ones.
+    assert_eq!(visibility.gaps[&0].len, 4);
+    assert_eq!(visibility.gaps[&0].reveal, [2, 3]);
+    assert_eq!(visibility.gaps[&9].reveal, [9]);
+}
+
+#[test]
+fn revealed_events_are_context() {
+    let state = State::new(&log()).unwrap();
+    let view_state = ViewState {
+        revealed: [2, 3].into(),
+        ..errors_only(2)
+    };
+    let visibility = Visibility::new(&state, &view_state);
+
+    assert_eq!(visibility.rows[2], RowState::Context);
+    assert_eq!(visibility.gaps[&0].reveal, [0, 1]);
+}
+
+#[test]
+fn export_keeps_displayed_events() {
+    let state = State::new(&log()).unwrap();
+    let visibility = Visibility::new(&state, &errors_only(1));
+
+    let exported = State::new(&visibility.export(&state)).unwrap();
+    let messages = exported
+        .events
+        .iter()
+        .map(|event| event.fields.message.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(messages, ["line 5", "line 6", "line 7"]);
+}
+
+#[test]
+fn around_takes_messages_of_the_same_span() {
+    let state = State::new(&log()).unwrap();
+
+    assert_eq!(around(&state, 6, 2), [4, 5, 6, 7, 8]);
+    assert_eq!(around(&state, 1, 3), [0, 1, 2, 3, 4]);
+}
@@ -0,0 +1,86 @@
+use std::collections::BTreeSet;
+
+use log_viewer::{
+    context::Visibility, expansion::Expansion, level_filter::LevelFilter, state::State,
+    view_state::ViewState,
+};
+
+// Node 1 is `flash` containing node 2 `erase`, node 3 `verify` only has trace events.
+const LOG: &str = r#"{"level":"INFO","target":"probe_rs","fields":{"message":"enter"},"span":{"name":"flash"},"spans":[{"name":"flash"}]}
+{"level":"INFO","target":"probe_rs","fields":{"message":"enter"},"span":{"name":"erase"},"spans":[{"name":"flash"},{"name":"erase"}]}
+{"level":"WARN","target":"probe_rs","fields":{"message":"sector locked"},"span":{"name":"erase"},"spans":[{"name":"flash"},{"name":"erase"}]}
+{"level":"INFO","target":"probe_rs","fields":{"message":"exit"},"span":{"name":"erase"},"spans":[{"name":"flash"}]}
+{"level":"INFO","target":"probe_rs","fields":{"message":"exit"},"span":{"name":"flash"},"spans":[]}
+{"level":"INFO","target":"probe_rs","fields":{"message":"enter"},"span":{"name":"verify"},"spans":[{"name":"verify"}]}
+{"level":"TRACE","target":"probe_rs","fields":{"message":"reading"},"span":{"name":"verify"},"spans":[{"name":"verify"}]}
+{"level":"INFO","target":"probe_rs","fields":{"message":"exit"},"span":{"name":"verify"},"spans":[]}
+"#;
+
+fn apply(expansion: Expansion, view_state: &ViewState, matches: &[usize]) -> BTreeSet<usize> {
+    let state = State::new(LOG).unwrap();
+    let visibility = Visibility::new(&state, view_state);
+    expansion.apply(&state, &visibility, matches, &view_state.expanded)
+}
+
+#[test]
+fn expand_and_collapse_all() {
+    let view_state = ViewState {
+        expanded: [1].into(),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        apply(Expansion::ExpandAll, &view_state, &[]),
+        [1, 2, 3].into()
+    );
+    assert!(apply(Expansion::CollapseAll, &view_state, &[]).is_empty());
+}
+
+#[test]
+fn expand_to_depth() {
+    let view_state = ViewState::default();
+
+    assert_eq!(
+        apply(Expansion::ExpandToDepth(1), &view_state, &[]),
+        [1, 3].into()
+    );
+    assert_eq!(
+        apply(Expansion::ExpandToDepth(2), &view_state, &[]),
+        [1, 2, 3].into()
+    );
+}
+
+#[test]
+fn expand_to_visible_matches_and_warnings() {
+    let view_state = ViewState::default();
+
+    assert_eq!(
+        apply(Expansion::ExpandToWarnings, &view_state, &[]),
+        [1, 2].into()
+    );
+    assert_eq!(
+        apply(Expansion::ExpandToMatches, &view_state, &[6]),
+        [3].into()
+    );
+
+    // Matches hidden by the filters don't count.
+    let view_state = ViewState {
+        filter: LevelFilter::from_directives("info").unwrap(),
+        ..Default::default()
+    };
+    assert!(apply(Expansion::ExpandToMatches, &view_state, &[6]).is_empty());
+}
+
+#[test]
+fn collapse_empty_keeps_spans_with_visible_events() {
+    let view_state = ViewState {
+        filter: LevelFilter::from_directives("info").unwrap(),
+        expanded: [1, 2, 3].into(),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        apply(Expansion::CollapseEmpty, &view_state, &[]),
+        [1, 2].into()
+    );
+}
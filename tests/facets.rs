@@ -0,0 +1,60 @@
+use log_viewer::{
+    facets::{facets, show_fields, FieldFilter, FieldFilterMode},
+    state::State,
+};
+
+const LOG: &str = r#"{"level":"INFO","target":"probe_rs","fields":{"message":"new"},"span":{"name":"attach","core":0},"spans":[]}
+{"level":"INFO","target":"probe_rs","fields":{"message":"enter"},"span":{"name":"attach","core":0},"spans":[{"name":"attach","core":0}]}
+{"level":"DEBUG","target":"probe_rs","fields":{"message":"halted","ap":1},"span":{"name":"attach","core":0},"spans":[{"name":"attach","core":0}]}
+{"level":"DEBUG","target":"probe_rs","fields":{"message":"halted","ap":2,"core":1},"span":{"name":"attach","core":0},"spans":[{"name":"attach","core":0}]}
+{"level":"INFO","target":"probe_rs","fields":{"message":"exit"},"span":{"name":"attach","core":0},"spans":[]}
+"#;
+
+fn filter(name: &str, value: &str, mode: FieldFilterMode) -> FieldFilter {
+    FieldFilter {
+        name: name.into(),
+        value: value.into(),
+        mode,
+    }
+}
+
+#[test]
+fn facets_count_event_and_span_fields() {
+    let state = State::new(LOG).unwrap();
+    let facets = facets(&state);
+
+    let names = facets.iter().map(|f| f.name.as_str()).collect::<Vec<_>>();
+    assert_eq!(names, ["ap", "core"]);
+    assert_eq!(facets[0].values, [("1".into(), 1), ("2".into(), 1)]);
+    // The event field overrides the one of its span.
+    assert_eq!(facets[1].values, [("0".into(), 1), ("1".into(), 1)]);
+}
+
+#[test]
+fn includes_of_one_field_are_alternatives() {
+    let state = State::new(LOG).unwrap();
+    let shown = |filters: &[FieldFilter]| {
+        (0..state.events.len())
+            .filter(|&index| state.is_message(index))
+            .filter(|&index| show_fields(filters, &state.events[index]))
+            .count()
+    };
+
+    assert_eq!(shown(&[]), 2);
+    assert_eq!(shown(&[filter("ap", "1", FieldFilterMode::Include)]), 1);
+    assert_eq!(
+        shown(&[
+            filter("ap", "1", FieldFilterMode::Include),
+            filter("ap", "2", FieldFilterMode::Include)
+        ]),
+        2
+    );
+    assert_eq!(
+        shown(&[
+            filter("ap", "2", FieldFilterMode::Include),
+            filter("core", "0", FieldFilterMode::Include)
+        ]),
+        0
+    );
+    assert_eq!(shown(&[filter("core", "1", FieldFilterMode::Exclude)]), 1);
+}
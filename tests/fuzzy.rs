@@ -0,0 +1,21 @@
+use log_viewer::fuzzy::fuzzy_score;
+
+#[test]
+fn characters_have_to_appear_in_order() {
+    assert!(fuzzy_score("exp", "Expand all").is_some());
+    assert!(fuzzy_score("EXALL", "Expand all").is_some());
+    assert!(fuzzy_score("", "Expand all").is_some());
+    assert!(fuzzy_score("pxe", "Expand all").is_none());
+    assert!(fuzzy_score("expand alls", "Expand all").is_none());
+}
+
+#[test]
+fn word_starts_and_runs_score_higher() {
+    let score = |pattern| fuzzy_score(pattern, "Toggle time mode").unwrap();
+
+    assert!(score("tm") > score("te"));
+    assert!(score("time") > score("tgie"));
+    assert!(
+        fuzzy_score("tt", "Toggle targets").unwrap() > fuzzy_score("tt", "Toggle facets").unwrap()
+    );
+}
@@ -0,0 +1,41 @@
+use log_viewer::gist_files::{CreateGist, Manifest, LOG_FILE, MANIFEST_FILE, MAX_FILE_SIZE};
+
+/// A log that barely compresses, so its encoding needs several parts.
+fn noisy_log(size: usize) -> String {
+    let mut seed = 0x2545_f491_4f6c_dd1du64;
+    (0..size)
+        .map(|_| {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            char::from(b' ' + (seed % 95) as u8)
+        })
+        .collect()
+}
+
+#[test]
+fn small_logs_are_stored_as_is() {
+    let log = r#"{"level":"INFO","fields":{"message":"attach"}}"#;
+    let gist = CreateGist::new(log, None).unwrap();
+
+    assert_eq!(gist.files.keys().collect::<Vec<_>>(), [LOG_FILE]);
+    assert_eq!(gist.files[LOG_FILE].content, log);
+}
+
+#[test]
+fn large_logs_are_split_and_decoded_back() {
+    let log = noisy_log(2 * MAX_FILE_SIZE);
+    let gist = CreateGist::new(&log, None).unwrap();
+
+    let manifest: Manifest = serde_json::from_str(&gist.files[MANIFEST_FILE].content).unwrap();
+    assert!(manifest.parts.len() > 1);
+    assert_eq!(gist.files.len(), manifest.parts.len() + 1);
+
+    let mut encoded = String::new();
+    for part in &manifest.parts {
+        let content = &gist.files[part].content;
+        assert!(content.len() <= MAX_FILE_SIZE);
+        encoded.push_str(content);
+    }
+    assert_eq!(manifest.decode(&encoded).unwrap(), log);
+}
@@ -0,0 +1,302 @@
+use log_viewer::{
+    level_filter::{LevelFilter, LevelSet, Levels, SpanRule},
+    proto::log_level::LogLevel,
+    view_state::ViewState,
+};
+
+#[test]
+fn directives_are_parsed() {
+    let filter =
+        LevelFilter::from_directives("info, probe_rs=DEBUG,jaylink=off,probe_rs::flashing")
+            .unwrap();
+
+    assert_eq!(filter.matrix().get(&None), Some(&LogLevel::Info));
+    assert_eq!(
+        filter.matrix().get(&Some("probe_rs".into())),
+        Some(&LogLevel::Debug)
+    );
+    assert_eq!(
+        filter.matrix().get(&Some("jaylink".into())),
+        Some(&LogLevel::None)
+    );
+    assert_eq!(
+        filter.matrix().get(&Some("probe_rs::flashing".into())),
+        Some(&LogLevel::Trace)
+    );
+}
+
+#[test]
+fn targets_without_a_default_hide_everything_else() {
+    let filter = LevelFilter::from_directives("probe_rs=debug").unwrap();
+    assert_eq!(filter.matrix().get(&None), Some(&LogLevel::None));
+
+    assert_eq!(
+        LevelFilter::from_directives("").unwrap(),
+        LevelFilter::default()
+    );
+}
+
+#[test]
+fn directives_round_trip() {
+    let directives = "warn,jaylink=off,probe_rs=debug,probe_rs::probe=trace";
+    let filter = LevelFilter::from_directives(directives).unwrap();
+
+    assert_eq!(filter.to_string(), directives);
+    assert_eq!(LevelFilter::default().to_string(), "trace");
+}
+
+#[test]
+fn invalid_directives_are_rejected() {
+    let error = LevelFilter::from_directives("info,probe_rs=loud").unwrap_err();
+    assert_eq!(error.directive, "probe_rs=loud");
+
+    assert!(LevelFilter::from_directives("=debug").is_err());
+    assert!(LevelFilter::from_directives("probe rs=debug").is_err());
+}
+
+fn filter(directives: &str) -> LevelFilter {
+    LevelFilter::from_directives(directives).unwrap()
+}
+
+#[test]
+fn default_shows_everything() {
+    let filter = LevelFilter::default();
+    assert!(filter.show("probe_rs", &LogLevel::Trace));
+    assert!(filter.show("", &LogLevel::Trace));
+}
+
+#[test]
+fn targets_match_at_module_boundaries() {
+    let filter = filter("info,probe_rs::probe=off");
+
+    assert!(!filter.show("probe_rs::probe", &LogLevel::Error));
+    assert!(!filter.show("probe_rs::probe::cmsisdap", &LogLevel::Error));
+    assert!(filter.show("probe_rs::probe_foo", &LogLevel::Info));
+    assert!(filter.show("probe_rs", &LogLevel::Info));
+    assert!(!filter.show("probe_rs", &LogLevel::Debug));
+}
+
+#[test]
+fn longest_matching_target_wins() {
+    // `probe_rs::flashing` is lexicographically larger but less specific.
+    let filter =
+        filter("off,probe_rs::architecture::arm=debug,probe_rs=warn,probe_rs::flashing=trace");
+
+    assert!(filter.show("probe_rs::architecture::arm::core", &LogLevel::Debug));
+    assert!(!filter.show("probe_rs::architecture::riscv", &LogLevel::Info));
+    assert!(filter.show("probe_rs::architecture::riscv", &LogLevel::Warn));
+    assert!(filter.show("probe_rs::flashing::flasher", &LogLevel::Trace));
+    assert!(!filter.show("jaylink", &LogLevel::Error));
+}
+
+#[test]
+fn more_specific_targets_can_show_more() {
+    let filter = filter("error,probe_rs=off,probe_rs::probe::cmsisdap=trace");
+
+    assert!(filter.show("probe_rs::probe::cmsisdap::commands", &LogLevel::Trace));
+    assert!(!filter.show("probe_rs::probe", &LogLevel::Error));
+    assert!(filter.show("jaylink", &LogLevel::Error));
+}
+
+#[test]
+fn changes_are_applied_to_lookups() {
+    let filter = filter("info")
+        .set_level(Some("probe_rs".into()), LogLevel::Trace)
+        .set_level(Some("probe_rs::probe".into()), LogLevel::None);
+    assert!(filter.show("probe_rs::flashing", &LogLevel::Trace));
+    assert!(!filter.show("probe_rs::probe", &LogLevel::Error));
+
+    let filter = filter.remove(&Some("probe_rs::probe".into()));
+    assert!(filter.show("probe_rs::probe", &LogLevel::Trace));
+
+    let filter = filter.remove(&None);
+    assert!(!filter.show("jaylink", &LogLevel::Error));
+}
+
+fn event(level: &str, target: &str, spans: serde_json::Value) -> log_viewer::proto::Event {
+    let span = spans.as_array().and_then(|spans| spans.last()).cloned();
+    serde_json::from_value(serde_json::json!({
+        "level": level,
+        "target": target,
+        "fields": { "message": "message" },
+        "span": span,
+        "spans": spans,
+    }))
+    .unwrap()
+}
+
+#[test]
+fn span_rules_are_parsed_and_formatted() {
+    let directives = "info,probe_rs[flash{core=0,sector}]=trace,[attach]=debug";
+    let filter = filter(directives);
+
+    let rules = filter.span_rules();
+    assert_eq!(rules.len(), 2);
+    assert_eq!(rules[0].target.as_deref(), Some("probe_rs"));
+    assert_eq!(rules[0].name, "flash");
+    assert_eq!(
+        rules[0].fields,
+        vec![("core".into(), Some("0".into())), ("sector".into(), None)]
+    );
+    assert_eq!(rules[1].level, LogLevel::Debug);
+
+    assert_eq!(filter.to_string(), directives);
+    assert_eq!(filter.span_rules()[1].to_string(), "[attach]=debug");
+
+    assert!(LevelFilter::from_directives("[flash=trace").is_err());
+    assert!(LevelFilter::from_directives("[flash{core=0]=trace").is_err());
+    assert!(LevelFilter::from_directives("[flash]=loud").is_err());
+}
+
+#[test]
+fn span_rule_values_can_be_quoted() {
+    let filter =
+        filter(r#"off,[flash{chip="a, b}]", path = "C:\\x \"y\"", core=0}]=trace,jaylink=warn"#);
+
+    let rules = filter.span_rules();
+    assert_eq!(rules.len(), 1);
+    assert_eq!(
+        rules[0].fields,
+        vec![
+            ("chip".into(), Some("a, b}]".into())),
+            ("path".into(), Some(r#"C:\x "y""#.into())),
+            ("core".into(), Some("0".into())),
+        ]
+    );
+    assert_eq!(
+        filter.matrix().get(&Some("jaylink".into())),
+        Some(&LogLevel::Warn)
+    );
+    assert_eq!(
+        filter.to_string(),
+        r#"off,jaylink=warn,[flash{chip="a, b}]",path="C:\\x \"y\"",core=0}]=trace"#
+    );
+
+    assert!(LevelFilter::from_directives(r#"[flash{chip="a}]=trace"#).is_err());
+    assert!(LevelFilter::from_directives(r#"[flash{chip="a"b}]=trace"#).is_err());
+    assert!(LevelFilter::from_directives(r#"[flash{chip="a\n"}]=trace"#).is_err());
+    assert!(LevelFilter::from_directives("[flash{chip=a=b}]=trace").is_err());
+}
+
+#[test]
+fn span_rules_apply_inside_matching_spans() {
+    let filter = filter("info,[flash]=trace");
+    let spans = serde_json::json!([{ "name": "session" }, { "name": "flash" }]);
+
+    assert!(filter.show_event(&event("TRACE", "probe_rs::flashing", spans)));
+    assert!(!filter.show_event(&event(
+        "DEBUG",
+        "probe_rs::flashing",
+        serde_json::json!([{ "name": "session" }])
+    )));
+    assert!(filter.show_event(&event("INFO", "probe_rs", serde_json::json!([]))));
+}
+
+#[test]
+fn innermost_matching_span_wins() {
+    let filter = filter("trace,[session]=warn,[flash{core=1}]=debug,[erase]=off");
+
+    let spans = serde_json::json!([{ "name": "session" }, { "name": "flash", "core": 1 }]);
+    assert!(filter.show_event(&event("DEBUG", "probe_rs", spans)));
+
+    let spans = serde_json::json!([{ "name": "session" }, { "name": "flash", "core": 0 }]);
+    assert!(!filter.show_event(&event("DEBUG", "probe_rs", spans)));
+
+    let spans = serde_json::json!([
+        { "name": "session" },
+        { "name": "flash", "core": 1 },
+        { "name": "erase" }
+    ]);
+    assert!(!filter.show_event(&event("ERROR", "probe_rs", spans)));
+}
+
+#[test]
+fn span_rules_respect_their_target() {
+    let filter = filter("info,probe_rs::flashing[flash]=trace");
+    let spans = serde_json::json!([{ "name": "flash" }]);
+
+    assert!(filter.show_event(&event(
+        "TRACE",
+        "probe_rs::flashing::flasher",
+        spans.clone()
+    )));
+    assert!(!filter.show_event(&event("TRACE", "probe_rs::probe", spans)));
+}
+
+#[test]
+fn level_sets_show_exactly_their_levels() {
+    let filter = filter("info,probe_rs=warn|trace");
+
+    assert!(filter.show("probe_rs::probe", &LogLevel::Trace));
+    assert!(filter.show("probe_rs::probe", &LogLevel::Warn));
+    assert!(!filter.show("probe_rs::probe", &LogLevel::Debug));
+    assert!(!filter.show("probe_rs::probe", &LogLevel::Error));
+    assert!(filter.show("jaylink", &LogLevel::Error));
+
+    assert_eq!(filter.to_string(), "info,probe_rs=trace|warn");
+    assert!(LevelFilter::from_directives("probe_rs=warn|loud").is_err());
+    assert!(LevelFilter::from_directives("probe_rs=warn|off").is_err());
+}
+
+#[test]
+fn level_sets_equal_to_a_threshold_stay_thresholds() {
+    let set = [LogLevel::Warn, LogLevel::Error].iter().collect();
+    let filter = LevelFilter::default().set_levels(Some("probe_rs".into()), set);
+
+    assert_eq!(
+        filter.levels(&Some("probe_rs".into())),
+        Some(Levels::AtLeast(LogLevel::Warn))
+    );
+    assert_eq!(filter.to_string(), "trace,probe_rs=warn");
+
+    let filter = filter.set_levels(Some("probe_rs".into()), LevelSet::default());
+    assert!(!filter.show("probe_rs", &LogLevel::Error));
+
+    let set = [LogLevel::Trace, LogLevel::Warn].iter().collect();
+    let filter = filter
+        .set_levels(Some("probe_rs".into()), set)
+        .set_level(Some("probe_rs".into()), LogLevel::Debug);
+    assert_eq!(
+        filter.levels(&Some("probe_rs".into())),
+        Some(Levels::AtLeast(LogLevel::Debug))
+    );
+}
+
+#[test]
+fn exact_span_rules_only_show_that_span() {
+    let flash = event(
+        "TRACE",
+        "probe_rs",
+        serde_json::json!([{ "name": "flash", "core": 1 }]),
+    );
+    let span = flash.span.clone().unwrap();
+    let filter = filter("off").add_span_rule(SpanRule::exact(&span, LogLevel::Trace));
+
+    assert_eq!(filter.to_string(), "off,[flash{core=1}]=trace");
+    assert!(filter.show_event(&flash));
+    let other = serde_json::json!([{ "name": "flash", "core": 0 }]);
+    assert!(!filter.show_event(&event("ERROR", "probe_rs", other)));
+}
+
+#[test]
+fn exact_span_rules_with_awkward_values_round_trip() {
+    let flash = event(
+        "TRACE",
+        "probe_rs",
+        serde_json::json!([{ "name": "flash", "chip": "a, b}", "path": "x=\"y\"\\z", "pad": " 1 " }]),
+    );
+    let span = flash.span.clone().unwrap();
+    let filter = filter("off").add_span_rule(SpanRule::exact(&span, LogLevel::Trace));
+    assert!(filter.show_event(&flash));
+
+    let parsed = LevelFilter::from_directives(&filter.to_string()).unwrap();
+    assert_eq!(parsed, filter);
+    assert!(parsed.show_event(&flash));
+
+    let view_state = ViewState {
+        filter,
+        ..Default::default()
+    };
+    let decoded = ViewState::from_query(&view_state.to_query()).unwrap();
+    assert!(decoded.filter.show_event(&flash));
+}
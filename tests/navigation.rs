@@ -0,0 +1,108 @@
+use log_viewer::{
+    context::Visibility,
+    navigation::{displayed_rows, find_span, Navigation},
+    state::State,
+    view_state::ViewState,
+};
+
+// Node 1 is `flash` containing node 2 `erase`, node 3 `verify` follows `flash`.
+const LOG: &str = r#"{"level":"INFO","target":"probe_rs","fields":{"message":"enter"},"span":{"name":"flash"},"spans":[{"name":"flash"}]}
+{"level":"INFO","target":"probe_rs","fields":{"message":"starting"},"span":{"name":"flash"},"spans":[{"name":"flash"}]}
+{"level":"INFO","target":"probe_rs","fields":{"message":"enter"},"span":{"name":"erase"},"spans":[{"name":"flash"},{"name":"erase"}]}
+{"level":"WARN","target":"probe_rs","fields":{"message":"sector locked"},"span":{"name":"erase"},"spans":[{"name":"flash"},{"name":"erase"}]}
+{"level":"INFO","target":"probe_rs","fields":{"message":"exit"},"span":{"name":"erase"},"spans":[{"name":"flash"}]}
+{"level":"INFO","target":"probe_rs","fields":{"message":"exit"},"span":{"name":"flash"},"spans":[]}
+{"level":"INFO","target":"probe_rs","fields":{"message":"enter"},"span":{"name":"verify"},"spans":[{"name":"verify"}]}
+{"level":"ERROR","target":"probe_rs","fields":{"message":"mismatch"},"span":{"name":"verify"},"spans":[{"name":"verify"}]}
+{"level":"INFO","target":"probe_rs","fields":{"message":"exit"},"span":{"name":"verify"},"spans":[]}
+"#;
+
+fn navigate(view_state: &ViewState, navigations: &[Navigation]) -> ViewState {
+    let state = State::new(LOG).unwrap();
+    let visibility = Visibility::new(&state, view_state);
+    navigations
+        .iter()
+        .fold(view_state.clone(), |view_state, navigation| {
+            navigation.apply(&state, &view_state, &visibility)
+        })
+}
+
+#[test]
+fn displayed_rows_skip_collapsed_spans() {
+    let state = State::new(LOG).unwrap();
+    let view_state = ViewState {
+        expanded: [1].into(),
+        ..Default::default()
+    };
+    let visibility = Visibility::new(&state, &view_state);
+
+    assert_eq!(
+        displayed_rows(&state, &view_state, &visibility),
+        [0, 1, 2, 6]
+    );
+}
+
+#[test]
+fn moves_through_displayed_rows() {
+    let view_state = ViewState::default();
+
+    let down = navigate(&view_state, &[Navigation::Down, Navigation::Down]);
+    assert_eq!(down.selected, Some(6));
+    // The last row stays selected.
+    assert_eq!(navigate(&down, &[Navigation::Down]).selected, Some(6));
+    assert_eq!(navigate(&down, &[Navigation::Up]).selected, Some(0));
+}
+
+#[test]
+fn expands_collapses_and_jumps_between_spans() {
+    let view_state = ViewState {
+        selected: Some(0),
+        ..Default::default()
+    };
+
+    let inside = navigate(&view_state, &[Navigation::Expand, Navigation::Expand]);
+    assert_eq!(inside.expanded, [1].into());
+    assert_eq!(inside.selected, Some(1));
+
+    let sibling = navigate(&inside, &[Navigation::NextSibling]);
+    assert_eq!(sibling.selected, Some(2));
+    assert_eq!(navigate(&sibling, &[Navigation::Parent]).selected, Some(0));
+
+    // Collapsing a message moves to its span, collapsing again closes it.
+    let closed = navigate(&inside, &[Navigation::Collapse, Navigation::Collapse]);
+    assert_eq!(closed.selected, Some(0));
+    assert!(closed.expanded.is_empty());
+
+    assert_eq!(
+        navigate(&view_state, &[Navigation::NextSibling]).selected,
+        Some(6)
+    );
+}
+
+#[test]
+fn jumps_to_warnings_and_errors() {
+    let view_state = ViewState::default();
+
+    let warning = navigate(&view_state, &[Navigation::NextWarning]);
+    assert_eq!(warning.selected, Some(3));
+    assert_eq!(warning.expanded, [1, 2].into());
+
+    assert_eq!(
+        navigate(&warning, &[Navigation::NextWarning]).selected,
+        Some(7)
+    );
+    // Going back from the first one wraps around.
+    assert_eq!(
+        navigate(&warning, &[Navigation::PreviousWarning]).selected,
+        Some(7)
+    );
+}
+
+#[test]
+fn finds_spans_by_name() {
+    let state = State::new(LOG).unwrap();
+
+    assert_eq!(find_span(&state, None, "erase"), Some(2));
+    assert_eq!(find_span(&state, Some(2), "erase"), Some(2));
+    assert_eq!(find_span(&state, None, "program"), None);
+}
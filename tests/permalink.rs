@@ -0,0 +1,22 @@
+use log_viewer::permalink::{parse_fragment, permalink};
+
+#[test]
+fn permalink_replaces_the_fragment() {
+    assert_eq!(
+        permalink("https://example.com/?gist=abc&f=info", 12),
+        "https://example.com/?gist=abc&f=info#e=12"
+    );
+    assert_eq!(
+        permalink("https://example.com/?gist=abc#e=3", 12),
+        "https://example.com/?gist=abc#e=12"
+    );
+}
+
+#[test]
+fn fragments_are_parsed() {
+    assert_eq!(parse_fragment("#e=1234"), Some(1234));
+    assert_eq!(parse_fragment("e=7"), Some(7));
+    assert_eq!(parse_fragment("#x=1&e=7"), Some(7));
+    assert_eq!(parse_fragment("#e=seven"), None);
+    assert_eq!(parse_fragment(""), None);
+}
@@ -0,0 +1,125 @@
+use log_viewer::{proto::Event, query::Query};
+
+fn event(level: &str, target: &str, message: &str) -> Event {
+    serde_json::from_value(serde_json::json!({
+        "timestamp": "2024-01-01T00:00:00.000000Z",
+        "level": level,
+        "target": target,
+        "fields": { "message": message, "addr": "0xe000edf0", "retries": 3 },
+        "span": { "name": "attach", "core": 0 },
+        "spans": [{ "name": "session" }, { "name": "attach", "core": 0 }],
+    }))
+    .unwrap()
+}
+
+fn matches(query: &str, event: &Event) -> bool {
+    Query::parse(query)
+        .expect("a valid query")
+        .expect("a non-empty query")
+        .matches(event)
+}
+
+#[test]
+fn compound_query_matches() {
+    let query = r#"level>=warn and target:probe_rs::flashing and msg~"timeout" and span:attach and field.addr=0xE000EDF0"#;
+
+    assert!(matches(
+        query,
+        &event(
+            "WARN",
+            "probe_rs::flashing::flasher",
+            "Timeout while erasing"
+        )
+    ));
+    assert!(!matches(
+        query,
+        &event(
+            "INFO",
+            "probe_rs::flashing::flasher",
+            "Timeout while erasing"
+        )
+    ));
+    assert!(!matches(
+        query,
+        &event("WARN", "probe_rs::flashingx", "Timeout while erasing")
+    ));
+    assert!(!matches(
+        query,
+        &event("WARN", "probe_rs::flashing", "Erased sector")
+    ));
+}
+
+#[test]
+fn operators_precedence_and_negation() {
+    let warning = event("WARN", "probe_rs::probe", "Transmit buffer: [1]");
+    let info = event("INFO", "probe_rs::flashing", "Done");
+
+    assert!(matches("level=warn or target=probe_rs::flashing", &info));
+    assert!(matches("not level<=info", &warning));
+    assert!(!matches("level!=warn and msg:transmit", &warning));
+    assert!(matches("level=info or level=warn and msg:nothing", &info));
+    assert!(!matches(
+        "(level=info or level=warn) and msg:nothing",
+        &info
+    ));
+    // Conditions next to each other are combined with `and`.
+    assert!(matches("msg:transmit target:probe_rs", &warning));
+    assert!(!matches("msg:transmit target:probe_rs::flashing", &warning));
+}
+
+#[test]
+fn fields_are_compared_as_numbers() {
+    let event = event("INFO", "probe_rs", "Retrying");
+
+    assert!(matches("field.retries>2", &event));
+    assert!(matches("field.retries=0x3", &event));
+    assert!(!matches("field.retries<3", &event));
+    assert!(matches("field.core=0", &event));
+    assert!(!matches("field.missing=0", &event));
+}
+
+#[test]
+fn large_addresses_are_compared_exactly() {
+    let event: Event = serde_json::from_value(serde_json::json!({
+        "timestamp": "2024-01-01T00:00:00.000000Z",
+        "level": "INFO",
+        "target": "probe_rs",
+        "fields": { "message": "read", "addr": "0xFFFFFFFFFFFFFFF0" },
+    }))
+    .unwrap();
+
+    assert!(matches("field.addr=0xFFFFFFFFFFFFFFF0", &event));
+    assert!(!matches("field.addr=0xFFFFFFFFFFFFFFF1", &event));
+    assert!(matches("field.addr<0xFFFFFFFFFFFFFFF1", &event));
+    assert!(!matches("field.addr>0xFFFFFFFFFFFFFFF0", &event));
+    assert!(matches("field.addr>1.5", &event));
+}
+
+#[test]
+fn bare_words_search_the_whole_event() {
+    let event = event("INFO", "probe_rs::probe", "Transmit buffer: [1]");
+
+    assert!(matches("buffer", &event));
+    assert!(matches(r#""transmit buffer""#, &event));
+    assert!(matches("session", &event));
+    assert!(!matches("timeout", &event));
+}
+
+#[test]
+fn empty_and_invalid_queries() {
+    assert!(Query::parse("  ").unwrap().is_none());
+
+    for query in [
+        "level>=loud",
+        "(level=warn",
+        "level=warn)",
+        "msg~\"(unclosed\"",
+        "msg:\"unterminated",
+        "target>probe_rs",
+        "level~warn",
+        "field.retries>many",
+        "level=warn and",
+    ] {
+        assert!(Query::parse(query).is_err(), "{query} should be rejected");
+    }
+}
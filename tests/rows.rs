@@ -0,0 +1,75 @@
+use log_viewer::{
+    annotations::{Annotation, Annotations},
+    context::Visibility,
+    level_filter::LevelFilter,
+    rows::{flatten, Row},
+    state::State,
+    view_state::ViewState,
+};
+
+// Node 1 is `flash` with three messages, only the warning passes a `warn` filter.
+const LOG: &str = r#"{"level":"INFO","target":"probe_rs","fields":{"message":"enter"},"span":{"name":"flash"},"spans":[{"name":"flash"}]}
+{"level":"INFO","target":"probe_rs","fields":{"message":"erasing"},"span":{"name":"flash"},"spans":[{"name":"flash"}]}
+{"level":"INFO","target":"probe_rs","fields":{"message":"programming"},"span":{"name":"flash"},"spans":[{"name":"flash"}]}
+{"level":"WARN","target":"probe_rs","fields":{"message":"slow write"},"span":{"name":"flash"},"spans":[{"name":"flash"}]}
+{"level":"INFO","target":"probe_rs","fields":{"message":"exit"},"span":{"name":"flash"},"spans":[]}
+{"level":"WARN","target":"probe_rs","fields":{"message":"detached"},"span":null,"spans":null}
+"#;
+
+#[test]
+fn collapsed_spans_only_show_their_header() {
+    let state = State::new(LOG).unwrap();
+    let view_state = ViewState::default();
+    let visibility = Visibility::new(&state, &view_state);
+
+    assert_eq!(
+        flatten(&state, &view_state, &visibility, &Annotations::default()),
+        [
+            Row::Span {
+                node: 1,
+                event: 0,
+                depth: 0,
+                expanded: false
+            },
+            Row::Message { event: 5, depth: 0 },
+        ]
+    );
+}
+
+#[test]
+fn expanded_spans_are_indented_with_gaps_and_annotations() {
+    let state = State::new(LOG).unwrap();
+    let view_state = ViewState {
+        expanded: [1].into(),
+        filter: LevelFilter::from_directives("warn").unwrap(),
+        context: 1,
+        ..Default::default()
+    };
+    let visibility = Visibility::new(&state, &view_state);
+    let annotations = Annotations::default().with(Annotation {
+        event: 3,
+        comment: "too slow".into(),
+        author: "alice".into(),
+        created_at: String::new(),
+    });
+
+    let rows = flatten(&state, &view_state, &visibility, &annotations);
+    assert_eq!(
+        rows[1..],
+        [
+            Row::Gap { event: 1, depth: 1 },
+            Row::Message { event: 2, depth: 1 },
+            Row::Message { event: 3, depth: 1 },
+            Row::Annotation {
+                event: 3,
+                number: 0,
+                depth: 1
+            },
+            Row::Message { event: 5, depth: 0 },
+        ]
+    );
+    assert_eq!(
+        rows.iter().filter_map(Row::selectable).collect::<Vec<_>>(),
+        [0, 2, 3, 5]
+    );
+}
@@ -0,0 +1,132 @@
+use log_viewer::{
+    proto::Event,
+    search::{find_matches, SearchQuery, TextFilter, TextFilterMode},
+    state::State,
+    view_state::ViewState,
+};
+
+fn event(target: &str, message: &str) -> Event {
+    serde_json::from_value(serde_json::json!({
+        "timestamp": "2024-01-01T00:00:00.000000Z",
+        "level": "DEBUG",
+        "target": target,
+        "fields": { "message": message },
+    }))
+    .unwrap()
+}
+
+const COMMANDS: &str = "probe_rs::probe::cmsisdap::commands";
+
+#[test]
+fn plain_search_ignores_case_and_escapes_the_pattern() {
+    let matcher = SearchQuery::new("buffer: [1").matcher().unwrap();
+    assert!(matcher.is_match("Transmit BUFFER: [1, 2]"));
+
+    let matcher = SearchQuery {
+        case_sensitive: true,
+        ..SearchQuery::new("buffer")
+    }
+    .matcher()
+    .unwrap();
+    assert!(!matcher.is_match("BUFFER"));
+}
+
+#[test]
+fn whole_word_search_skips_partial_words() {
+    let query = SearchQuery {
+        whole_word: true,
+        ..SearchQuery::new("attach")
+    };
+    let matcher = query.matcher().unwrap();
+    assert!(matcher.is_match("attach to core"));
+    assert!(!matcher.is_match("attached"));
+}
+
+#[test]
+fn invalid_regex_is_reported() {
+    let query = SearchQuery {
+        regex: true,
+        ..SearchQuery::new("(unclosed")
+    };
+    assert!(query.matcher().is_err());
+}
+
+#[test]
+fn hide_filter_only_applies_to_its_target() {
+    let filter = TextFilter {
+        query: SearchQuery {
+            regex: true,
+            ..SearchQuery::new("^(Transmit|Receive) buffer:")
+        },
+        mode: TextFilterMode::Hide,
+        target: Some(COMMANDS.into()),
+        message_only: false,
+    };
+
+    assert!(!filter.show(&event(COMMANDS, "Transmit buffer: [1, 2]")));
+    assert!(!filter.show(&event(COMMANDS, "Receive buffer: [3]")));
+    assert!(filter.show(&event(COMMANDS, "Sending command")));
+    assert!(filter.show(&event("probe_rs::flashing", "Transmit buffer: [1]")));
+    assert!(filter.show(&event(
+        "probe_rs::probe::cmsisdap::commandsx",
+        "Transmit buffer:"
+    )));
+}
+
+#[test]
+fn only_filter_hides_everything_else() {
+    let filter = TextFilter {
+        query: SearchQuery::new("timeout"),
+        mode: TextFilterMode::Only,
+        target: None,
+        message_only: false,
+    };
+
+    assert!(filter.show(&event("probe_rs", "Timeout while waiting")));
+    assert!(!filter.show(&event("probe_rs", "Attached")));
+}
+
+#[test]
+fn message_filters_ignore_the_rest_of_the_event() {
+    let filter = TextFilter {
+        query: SearchQuery::exact("probe_rs"),
+        mode: TextFilterMode::Hide,
+        target: None,
+        message_only: true,
+    };
+
+    assert!(!filter.show(&event("jaylink", "probe_rs")));
+    assert!(filter.show(&event("probe_rs", "Attached")));
+    assert_eq!(filter.to_string(), "hide message \"^probe_rs$\"");
+}
+
+#[test]
+fn exact_query_matches_only_the_whole_text() {
+    let matcher = SearchQuery::exact("read 4 bytes (ok?)").matcher().unwrap();
+
+    assert!(matcher.is_match("read 4 bytes (ok?)"));
+    assert!(!matcher.is_match("read 4 bytes (ok?) twice"));
+    assert!(!matcher.is_match("Read 4 bytes (ok?)"));
+}
+
+#[test]
+fn span_headers_only_match_their_span() {
+    let log = r#"{"level":"INFO","target":"probe_rs","fields":{"message":"enter"},"span":{"name":"flash","core":0},"spans":[{"name":"flash","core":0}]}
+{"level":"INFO","target":"probe_rs","fields":{"message":"erasing"},"span":{"name":"flash","core":0},"spans":[{"name":"flash","core":0}]}
+{"level":"INFO","target":"probe_rs","fields":{"message":"exit"},"span":{"name":"flash","core":0},"spans":[]}
+{"level":"INFO","target":"jaylink","fields":{"message":"enter done"},"span":null,"spans":null}
+"#;
+    let state = State::new(log).unwrap();
+    let matches = |pattern: &str| {
+        let view_state = ViewState {
+            search: SearchQuery::new(pattern),
+            ..Default::default()
+        };
+        find_matches(&state, &view_state).unwrap()
+    };
+
+    assert_eq!(matches("probe_rs"), [1]);
+    assert_eq!(matches("enter"), [3]);
+    assert_eq!(matches("flash"), [0, 1]);
+    assert_eq!(matches("core"), [0, 1]);
+}
@@ -0,0 +1,22 @@
+use log_viewer::state::State;
+
+#[test]
+fn events_remember_their_lines() {
+    let log = r#"Flashing...
+{"level":"INFO","target":"probe_rs","fields":{"message":"started"},"span":null,"spans":null}
+{"level":"INFO","target":"probe_rs","fields":{"message":"broken"
+{"level":"INFO","target":"probe_rs","fields":{"message":"done"},"span":null,"spans":null}
+"#;
+    let state = State::new(log).unwrap();
+
+    assert_eq!(state.lines, [1, 3]);
+    assert_eq!(state.skipped.len(), 1);
+    assert_eq!(state.skipped[0].line_no, 2);
+    assert_eq!(
+        state.raw_line(log, 1),
+        Some(
+            r#"{"level":"INFO","target":"probe_rs","fields":{"message":"done"},"span":null,"spans":null}"#
+        )
+    );
+    assert_eq!(state.raw_line(log, 2), None);
+}
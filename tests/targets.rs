@@ -0,0 +1,44 @@
+use log_viewer::{
+    context::Visibility, level_filter::LevelFilter, state::State, targets::TargetNode,
+    view_state::ViewState,
+};
+
+const LOG: &str = r#"{"level":"TRACE","target":"probe_rs::probe::cmsisdap::commands","fields":{"message":"Transmit buffer"},"span":null,"spans":null}
+{"level":"TRACE","target":"probe_rs::probe::cmsisdap::commands","fields":{"message":"Receive buffer"},"span":null,"spans":null}
+{"level":"DEBUG","target":"probe_rs::probe::cmsisdap","fields":{"message":"Attached"},"span":null,"spans":null}
+{"level":"WARN","target":"probe_rs::flashing","fields":{"message":"Slow erase"},"span":null,"spans":null}
+{"level":"INFO","target":"jaylink","fields":{"message":"Found probe"},"span":null,"spans":null}
+"#;
+
+#[test]
+fn tree_counts_events_per_level_and_visibility() {
+    let state = State::new(LOG).unwrap();
+    let view_state = ViewState {
+        filter: LevelFilter::from_directives("debug").unwrap(),
+        ..Default::default()
+    };
+    let root = TargetNode::new(&state, &Visibility::new(&state, &view_state));
+
+    assert_eq!(root.total(), 5);
+    assert_eq!(root.visible, 3);
+
+    let names = root
+        .children
+        .iter()
+        .map(|n| n.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["probe_rs", "jaylink"]);
+
+    let probe_rs = &root.children[0];
+    assert_eq!(probe_rs.counts, [2, 1, 0, 1, 0]);
+    assert_eq!(probe_rs.hidden(), 2);
+
+    let cmsisdap = &probe_rs.children[0].children[0];
+    assert_eq!(cmsisdap.path, "probe_rs::probe::cmsisdap");
+    assert_eq!(cmsisdap.total(), 3);
+    assert_eq!(
+        cmsisdap.children[0].path,
+        "probe_rs::probe::cmsisdap::commands"
+    );
+    assert_eq!(cmsisdap.children[0].visible, 0);
+}
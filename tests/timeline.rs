@@ -0,0 +1,59 @@
+use log_viewer::{
+    state::State,
+    timeline::{Density, TimeRange},
+    view_state::ViewState,
+};
+
+const LOG: &str = r#"{"timestamp":"2024-01-01T00:00:00.000000Z","level":"INFO","target":"probe_rs","fields":{"message":"started"},"span":null,"spans":null}
+{"timestamp":"2024-01-01T00:00:01.000000Z","level":"INFO","target":"probe_rs","fields":{"message":"enter"},"span":{"name":"flash"},"spans":[{"name":"flash"}]}
+{"timestamp":"2024-01-01T00:00:02.000000Z","level":"INFO","target":"probe_rs","fields":{"message":"erasing"},"span":{"name":"flash"},"spans":[{"name":"flash"}]}
+{"timestamp":"2024-01-01T00:00:05.000000Z","level":"INFO","target":"probe_rs","fields":{"message":"exit"},"span":{"name":"flash"},"spans":[]}
+{"timestamp":"2024-01-01T00:00:10.000000Z","level":"INFO","target":"probe_rs","fields":{"message":"done"},"span":null,"spans":null}
+"#;
+
+const START: i64 = 1_704_067_200_000_000;
+const SECOND: i64 = 1_000_000;
+
+#[test]
+fn spans_cover_their_events_and_exit() {
+    let state = State::new(LOG).unwrap();
+
+    assert_eq!(
+        state.nodes[1].time,
+        Some(TimeRange::new(START + SECOND, START + 5 * SECOND))
+    );
+    assert_eq!(
+        state.nodes[0].time,
+        Some(TimeRange::new(START, START + 10 * SECOND))
+    );
+}
+
+#[test]
+fn range_hides_events_but_keeps_overlapping_spans() {
+    let state = State::new(LOG).unwrap();
+    let view_state = ViewState {
+        time_range: Some(TimeRange::new(START + 3 * SECOND, START + 4 * SECOND)),
+        ..Default::default()
+    };
+
+    assert!(!view_state.shows(&state.events[0]));
+    assert!(!view_state.shows(&state.events[2]));
+    assert!(view_state.shows_span(&state.nodes[1]));
+
+    let view_state = ViewState {
+        time_range: Some(TimeRange::new(START + 6 * SECOND, START + 10 * SECOND)),
+        ..Default::default()
+    };
+    assert!(!view_state.shows_span(&state.nodes[1]));
+    assert!(view_state.shows(&state.events[4]));
+}
+
+#[test]
+fn density_counts_events_per_bucket() {
+    let state = State::new(LOG).unwrap();
+    let density = Density::new(&state, 10).unwrap();
+
+    assert_eq!(density.counts, [1, 1, 1, 0, 0, 1, 0, 0, 0, 1]);
+    assert_eq!(density.time_at(0.5), START + 5 * SECOND);
+    assert_eq!(density.fraction_of(START + 2 * SECOND), 0.2);
+}
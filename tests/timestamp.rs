@@ -0,0 +1,16 @@
+use log_viewer::timestamp::parse_timestamp;
+
+#[test]
+fn parses_fractions_and_offsets() {
+    assert_eq!(parse_timestamp("1970-01-01T00:00:01.5Z"), Some(1_500_000));
+    assert_eq!(
+        parse_timestamp("1970-01-01T01:00:00.000001234+01:00"),
+        Some(1)
+    );
+}
+
+#[test]
+fn invalid_fractions_are_rejected() {
+    assert_eq!(parse_timestamp("1970-01-01T00:00:00.12345éZ"), None);
+    assert_eq!(parse_timestamp("1970-01-01T00:00:00.1a3Z"), None);
+}
@@ -0,0 +1,188 @@
+use std::collections::BTreeSet;
+
+use log_viewer::{
+    facets::{FieldFilter, FieldFilterMode},
+    level_filter::LevelFilter,
+    proto::log_level::LogLevel,
+    search::{SearchQuery, TextFilter, TextFilterMode},
+    timeline::TimeRange,
+    view_state::{Source, TimeMode, ViewState},
+};
+
+fn round_trip(state: &ViewState) -> ViewState {
+    ViewState::from_query(&state.to_query()).expect("decoding an encoded state")
+}
+
+#[test]
+fn default_state_round_trips() {
+    let state = ViewState::default();
+    assert_eq!(round_trip(&state), state);
+    assert_eq!(state.to_query(), "v=2&f=trace");
+}
+
+#[test]
+fn full_state_round_trips() {
+    let state = ViewState {
+        source: Some(Source {
+            gist: "14a826cbe3a884fc3207cde3dfd38817".into(),
+            revision: Some("0f1e2d3c".into()),
+        }),
+        filter: LevelFilter::default()
+            .set_level(None, LogLevel::Warn)
+            .set_level(Some("probe_rs::probe".into()), LogLevel::Debug)
+            .set_level(Some("probe-rs-debug::server".into()), LogLevel::None),
+        search: SearchQuery {
+            pattern: "Transmit buffer: [1, 2] & 50% + more=ü".into(),
+            regex: true,
+            case_sensitive: true,
+            whole_word: false,
+        },
+        text_filters: vec![
+            TextFilter {
+                query: SearchQuery {
+                    pattern: "^(Transmit|Receive) buffer: a/b".into(),
+                    regex: true,
+                    ..Default::default()
+                },
+                mode: TextFilterMode::Hide,
+                target: Some("probe_rs::probe::cmsisdap::commands".into()),
+                message_only: false,
+            },
+            TextFilter {
+                query: SearchQuery::new("attach"),
+                mode: TextFilterMode::Only,
+                target: None,
+                message_only: true,
+            },
+        ],
+        field_filters: vec![
+            FieldFilter {
+                name: "core".into(),
+                value: "0".into(),
+                mode: FieldFilterMode::Include,
+            },
+            FieldFilter {
+                name: "log.module_path".into(),
+                value: "probe_rs/probe".into(),
+                mode: FieldFilterMode::Exclude,
+            },
+        ],
+        query: r#"level>=warn and (msg~"time(out)?" or field.addr=0xE000EDF0)"#.into(),
+        time_range: Some(TimeRange::new(1_700_000_000_000_000, 1_700_000_002_500_000)),
+        context: 20,
+        revealed: BTreeSet::from([40, 41, 42]),
+        expanded: BTreeSet::from([1, 2, 3, 4, 7, 10, 11]),
+        selected: Some(1234),
+        time_mode: TimeMode::Relative,
+    };
+
+    assert_eq!(round_trip(&state), state);
+}
+
+#[test]
+fn expanded_nodes_are_encoded_as_ranges() {
+    let state = ViewState {
+        expanded: BTreeSet::from([1, 2, 3, 4, 7, 9, 10]),
+        ..Default::default()
+    };
+
+    assert!(state.to_query().contains("x=1-4.7.9-10"));
+}
+
+#[test]
+fn targets_containing_dashes_are_kept() {
+    let state = ViewState {
+        filter: LevelFilter::default().set_level(Some("my-crate::a-b".into()), LogLevel::Info),
+        ..Default::default()
+    };
+
+    let decoded = round_trip(&state);
+    assert_eq!(
+        decoded.filter.matrix().get(&Some("my-crate::a-b".into())),
+        Some(&LogLevel::Info)
+    );
+}
+
+#[test]
+fn filters_are_encoded_as_directives() {
+    let state = ViewState {
+        filter: LevelFilter::default()
+            .set_level(None, LogLevel::Info)
+            .set_level(Some("probe_rs".into()), LogLevel::Debug)
+            .set_level(Some("jaylink".into()), LogLevel::None),
+        ..Default::default()
+    };
+
+    assert!(state
+        .to_query()
+        .contains("f=info,jaylink=off,probe_rs=debug"));
+    assert_eq!(round_trip(&state), state);
+}
+
+#[test]
+fn version_1_links_are_decoded() {
+    let state = ViewState::from_query("v=1&f=warn&f.probe_rs::probe=debug&q=attach").unwrap();
+
+    assert_eq!(
+        state.filter,
+        LevelFilter::from_directives("warn,probe_rs::probe=debug").unwrap()
+    );
+    assert_eq!(state.search.pattern, "attach");
+}
+
+#[test]
+fn legacy_links_are_decoded() {
+    let state = ViewState::from_query("?gist=abc&filter=info&filter-probe_rs-probe=none").unwrap();
+
+    assert_eq!(
+        state.source,
+        Some(Source {
+            gist: "abc".into(),
+            revision: None
+        })
+    );
+    assert_eq!(state.filter.matrix().get(&None), Some(&LogLevel::Info));
+    assert_eq!(
+        state.filter.matrix().get(&Some("probe_rs::probe".into())),
+        Some(&LogLevel::None)
+    );
+}
+
+#[test]
+fn invalid_parameters_are_reported() {
+    let error = ViewState::from_query("v=1&f.probe_rs=loud").unwrap_err();
+    assert_eq!(error.key, "f.probe_rs");
+    assert_eq!(error.value, "loud");
+
+    let error = ViewState::from_query("v=2&f=info,probe_rs=loud").unwrap_err();
+    assert_eq!(error.key, "f");
+
+    assert!(ViewState::from_query("v=2&x=1-a").is_err());
+    assert!(ViewState::from_query("v=2&x=5-2").is_err());
+    let error = ViewState::from_query("v=2&x=0-18446744073709551615").unwrap_err();
+    assert_eq!(error.key, "x");
+    let error = ViewState::from_query("v=2&rv=0-999999.2000000-2999999").unwrap_err();
+    assert_eq!(error.key, "rv");
+
+    let error = ViewState::from_query("v=2&q=%ZZ").unwrap_err();
+    assert_eq!(error.key, "q");
+    assert_eq!(error.value, "%ZZ");
+    assert!(ViewState::from_query("v=1&t=z").is_err());
+    let error = ViewState::from_query("v=1&rev=abc").unwrap_err();
+    assert_eq!((error.key.as_str(), error.value.as_str()), ("rev", "abc"));
+    let error = ViewState::from_query("v=2&gist=").unwrap_err();
+    assert_eq!((error.key.as_str(), error.value.as_str()), ("gist", ""));
+    assert!(ViewState::from_query("v=1&q=a&qo=z").is_err());
+    assert!(ViewState::from_query("v=1&tf=x//a").is_err());
+    assert!(ViewState::from_query("v=1&tf=h").is_err());
+    assert!(ViewState::from_query("v=2&ff=x/core/0").is_err());
+    assert!(ViewState::from_query("v=2&ff=i/core").is_err());
+    assert!(ViewState::from_query("v=2&tr=5").is_err());
+    assert!(ViewState::from_query("v=2&c=-1").is_err());
+}
+
+#[test]
+fn unknown_versions_are_rejected() {
+    let error = ViewState::from_query("v=99&gist=abc").unwrap_err();
+    assert_eq!(error.key, "v");
+}